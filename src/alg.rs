@@ -0,0 +1,368 @@
+//! Algorithms as sequences of moves in their own right, rather than as ways to reach a state: the
+//! same algorithm for the other hand, backwards, or for the cube held another way round.
+
+use crate::cube::{Cube, CORNER_FACELETS};
+use crate::dist_table::rank_cube;
+use crate::masked::facelet_sources_full;
+use crate::moves::{
+    flipped_full, mirrored_full, rotated_full, u_turns, Amt, Axis, CanFullMove, FullMove, Plane,
+    Rotation,
+};
+use crate::permutation::CornerPermutation;
+use crate::setup::parse_rotations;
+use crate::symmetry::{all_symmetries, arrangements_cube, plane_mirror, rotations};
+
+/// One version of an algorithm, and whether a cube agrees it does what it should
+pub struct Transformed {
+    pub name: String,
+    pub moves: Vec<FullMove>,
+    pub verified: bool,
+}
+
+/// Doing the alg and then its inverse should leave every facelet where it started
+pub fn check_inverse(start: &Cube, alg: &[FullMove], inverse: &[FullMove]) -> bool {
+    start.clone().apply_many_full(alg).apply_many_full(inverse) == *start
+}
+
+/// Doing the mirrored alg on the mirror image of a cube, then looking back through the mirror,
+/// should be the same as doing the alg itself
+pub fn check_mirror(start: &Cube, alg: &[FullMove], mirrored: &[FullMove], plane: Plane) -> bool {
+    let mirror = plane_mirror(plane);
+
+    let seen = mirror.turn(start).apply_many_full(mirrored);
+    mirror.inverse().turn(&seen) == start.clone().apply_many_full(alg)
+}
+
+/// Turning the cube, doing the alg, and turning it back should be the same as the rotated alg
+pub fn check_rotation(
+    start: &Cube,
+    alg: &[FullMove],
+    rotated: &[FullMove],
+    rotations: &[Rotation],
+) -> bool {
+    let there: Vec<FullMove> = rotations.iter().flat_map(|r| r.as_moves()).collect();
+    let back = flipped_full(&there);
+
+    let turned = start
+        .clone()
+        .apply_many_full(&there)
+        .apply_many_full(alg)
+        .apply_many_full(&back);
+
+    turned == start.clone().apply_many_full(rotated)
+}
+
+/// The inverse, the three mirrors, the alg from each other side around y, and the alg for the
+/// cube turned by `extra` (if there is any), each checked on the start cube
+pub fn transforms(start: &Cube, alg: &[FullMove], extra: &[Rotation]) -> Vec<Transformed> {
+    let mut out = Vec::new();
+
+    let inverse = flipped_full(alg);
+    out.push(Transformed {
+        name: "Inverse".to_string(),
+        verified: check_inverse(start, alg, &inverse),
+        moves: inverse,
+    });
+
+    for (plane, name) in [
+        (Plane::LR, "Mirror (L/R)"),
+        (Plane::UD, "Mirror (U/D)"),
+        (Plane::FB, "Mirror (F/B)"),
+    ] {
+        let mirrored = mirrored_full(alg, plane);
+        out.push(Transformed {
+            name: name.to_string(),
+            verified: check_mirror(start, alg, &mirrored, plane),
+            moves: mirrored,
+        });
+    }
+
+    let mut rotate = |rotations: &[Rotation]| {
+        let rotated = rotated_full(alg, rotations);
+        let name = rotations
+            .iter()
+            .map(Rotation::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        out.push(Transformed {
+            name: format!("From {}", name),
+            verified: check_rotation(start, alg, &rotated, rotations),
+            moves: rotated,
+        });
+    };
+
+    for amt in [Amt::One, Amt::Two, Amt::Rev] {
+        rotate(&[Rotation(Axis::Y, amt)]);
+    }
+
+    if !extra.is_empty() {
+        rotate(extra);
+    }
+
+    out
+}
+
+/// How alike two algorithms are, from the closest on down. The U turns go before and after the
+/// first algorithm, to make it do the same as the second.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Equivalence {
+    /// They do exactly the same thing (though the whole cube may end up held another way round)
+    Exact,
+    Auf {
+        pre: Vec<FullMove>,
+        post: Vec<FullMove>,
+    },
+    /// The first, done with the cube held another way round (see [rotated_full])
+    Rotation {
+        rotation: Vec<Rotation>,
+        pre: Vec<FullMove>,
+        post: Vec<FullMove>,
+    },
+    /// The states they make are symmetric (see [crate::symmetry]), so the second is the first
+    /// with the colors painted differently; the symmetry may include a mirror
+    Recolor {
+        symmetry: String,
+        pre: Vec<FullMove>,
+        post: Vec<FullMove>,
+    },
+    Different,
+}
+
+/// Every way to do U turns before and after, doing nothing first, then just one side
+fn aufs() -> Vec<(Vec<FullMove>, Vec<FullMove>)> {
+    let mut out: Vec<(usize, usize)> = (0..4)
+        .flat_map(|pre| (0..4).map(move |post| (pre, post)))
+        .collect();
+    out.sort_by_key(|&(pre, post)| (pre > 0) as usize + (post > 0) as usize);
+
+    out.into_iter()
+        .map(|(pre, post)| (u_turns(pre), u_turns(post)))
+        .collect()
+}
+
+fn adjusted(pre: &[FullMove], alg: &[FullMove], post: &[FullMove]) -> Vec<FullMove> {
+    [pre, alg, post].concat()
+}
+
+/// Whether the two do the same to a solved cube, not minding how it ends up held
+fn same_effect(solved: &Cube, a: &[FullMove], b: &[FullMove]) -> bool {
+    solved
+        .clone()
+        .apply_many_full(a)
+        .apply_many_full(&flipped_full(b))
+        .solved()
+}
+
+/// How `a` has to be changed to do the same as `b`, starting from a solved cube
+pub fn equivalence(solved: &Cube, a: &[FullMove], b: &[FullMove]) -> Equivalence {
+    let aufs = aufs();
+
+    if same_effect(solved, a, b) {
+        return Equivalence::Exact;
+    }
+
+    for (pre, post) in &aufs {
+        if same_effect(solved, &adjusted(pre, a, post), b) {
+            return Equivalence::Auf {
+                pre: pre.clone(),
+                post: post.clone(),
+            };
+        }
+    }
+
+    for symmetry in rotations().iter().skip(1) {
+        let rotation = parse_rotations(symmetry.name()).expect("Rotations are named by turns");
+        let rotated = rotated_full(a, &rotation);
+
+        for (pre, post) in &aufs {
+            if same_effect(solved, &adjusted(pre, &rotated, post), b) {
+                return Equivalence::Rotation {
+                    rotation,
+                    pre: pre.clone(),
+                    post: post.clone(),
+                };
+            }
+        }
+    }
+
+    // symmetries go by the DLB corner, so hold the cube with that back where it started first
+    let fixed = |moves: &[FullMove]| {
+        let cube = solved.clone().apply_many_full(moves);
+        arrangements_cube(
+            &cube.clone().make_pos_arr_from_dlb(),
+            &cube.make_orr_arr_from_dlb(),
+        )
+    };
+    let target = rank_cube(&fixed(b));
+
+    for (pre, post) in &aufs {
+        let cube = fixed(&adjusted(pre, a, post));
+
+        if let Some(symmetry) = all_symmetries()
+            .into_iter()
+            .find(|s| rank_cube(&s.apply(&cube)) == target)
+        {
+            return Equivalence::Recolor {
+                symmetry: symmetry.name().to_string(),
+                pre: pre.clone(),
+                post: post.clone(),
+            };
+        }
+    }
+
+    Equivalence::Different
+}
+
+/// How many times the alg has to be done to bring a solved cube back to solved. When it leaves DLB
+/// where it was, that's the order of what it does to the corners; otherwise the cube gets held
+/// differently each time round, so it's easiest to just keep doing it.
+pub fn order(solved: &Cube, alg: &[FullMove]) -> usize {
+    let sources = facelet_sources_full(alg);
+    if CORNER_FACELETS[0].iter().all(|&i| sources[i] == i) {
+        let cube = solved.clone().apply_many_full(alg);
+        return CornerPermutation::from_cube(&cube).order();
+    }
+
+    let mut cube = solved.clone().apply_many_full(alg);
+    let mut repeats = 1;
+    while !cube.solved() {
+        cube = cube.apply_many_full(alg);
+        repeats += 1;
+    }
+
+    repeats
+}
+
+#[cfg(test)]
+mod alg_tests {
+    use crate::color_scheme::ColorScheme;
+    use crate::moves::nice_write;
+    use crate::scramble::full_scramble;
+    use crate::setup::{parse_line, parse_rotations};
+
+    use super::*;
+
+    #[test]
+    fn known_transforms() {
+        let sexy = parse_line("R U R' U'").unwrap();
+
+        assert_eq!(nice_write(&flipped_full(&sexy)), "U R U' R'");
+        assert_eq!(nice_write(&mirrored_full(&sexy, Plane::LR)), "L' U' L U");
+        assert_eq!(nice_write(&mirrored_full(&sexy, Plane::UD)), "R' D' R D");
+        assert_eq!(nice_write(&mirrored_full(&sexy, Plane::FB)), "R' U' R U");
+
+        let y = parse_rotations("y").unwrap();
+        assert_eq!(nice_write(&rotated_full(&sexy, &y)), "B U B' U'");
+
+        let x = parse_rotations("x").unwrap();
+        assert_eq!(nice_write(&rotated_full(&sexy, &x)), "R F R' F'");
+
+        // x then y is y first on the moves, then x on what that gives
+        let both = parse_rotations("x y").unwrap();
+        assert_eq!(nice_write(&rotated_full(&sexy, &both)), "U F U' F'");
+    }
+
+    #[test]
+    fn transforms_check_out() {
+        let alg = parse_line("R U2 R' U' R U' R' F2 L D' B").unwrap();
+        let extra = parse_rotations("x z' y2").unwrap();
+
+        for start in [
+            ColorScheme::western().solved_cube(),
+            full_scramble(&ColorScheme::western()),
+        ] {
+            let all = transforms(&start, &alg, &extra);
+            assert_eq!(all.len(), 8);
+            assert!(all.iter().all(|t| t.verified));
+        }
+
+        // and a wrong answer doesn't
+        let solved = ColorScheme::western().solved_cube();
+        let wrong = parse_line("L U R").unwrap();
+        assert!(!check_mirror(&solved, &alg, &wrong, Plane::LR));
+        assert!(!check_rotation(&solved, &alg, &wrong, &extra));
+        assert!(!check_inverse(&solved, &alg, &wrong));
+    }
+
+    #[test]
+    fn orders_match_repeating() {
+        let solved = ColorScheme::western().solved_cube();
+
+        for alg in [
+            "",
+            "R",
+            "R U",
+            "R U R' U R U2 R'",
+            "R U2 F' D L2 B",
+            "L' D B2",
+            "R L'",
+        ] {
+            let moves = parse_line(alg).unwrap();
+
+            let mut cube = solved.clone().apply_many_full(&moves);
+            let mut repeats = 1;
+            while !cube.solved() {
+                cube = cube.apply_many_full(&moves);
+                repeats += 1;
+            }
+
+            assert_eq!(order(&solved, &moves), repeats, "{}", alg);
+        }
+    }
+
+    #[test]
+    fn equivalent_algs() {
+        let solved = ColorScheme::western().solved_cube();
+        let equiv = |a: &str, b: &str| {
+            equivalence(&solved, &parse_line(a).unwrap(), &parse_line(b).unwrap())
+        };
+
+        assert_eq!(equiv("R U R' U'", "R U R' U'"), Equivalence::Exact);
+        assert_eq!(equiv("R", "L"), Equivalence::Exact);
+        assert_eq!(equiv("R U2 R'", "R U R' R U R'"), Equivalence::Exact);
+
+        // sune, ending with a U turn more
+        let sune = "R U R' U R U2 R'";
+        assert_eq!(
+            equiv(sune, "R U R' U R U2 R' U"),
+            Equivalence::Auf {
+                pre: vec![],
+                post: parse_line("U").unwrap(),
+            }
+        );
+
+        // from the left it's only a U turn away (the D layer doesn't count), but with the cube
+        // tipped over it takes a rotation
+        assert_eq!(
+            equiv(sune, "F U F' U F U2 F'"),
+            Equivalence::Auf {
+                pre: parse_line("U'").unwrap(),
+                post: parse_line("U").unwrap(),
+            }
+        );
+        match equiv(sune, "R F R' F R F2 R'") {
+            Equivalence::Rotation {
+                rotation,
+                pre,
+                post,
+            } => {
+                let rotated = rotated_full(&parse_line(sune).unwrap(), &rotation);
+                assert!(same_effect(
+                    &solved,
+                    &adjusted(&pre, &rotated, &post),
+                    &parse_line("R F R' F R F2 R'").unwrap()
+                ));
+            }
+            other => panic!("Expected a rotation, got {:?}", other),
+        }
+
+        // the left handed sune is a mirror image, which no rotation gets to
+        match equiv(sune, "L' U' L U' L' U2 L") {
+            Equivalence::Recolor { symmetry, .. } => assert!(symmetry.starts_with("mirror")),
+            other => panic!("Expected a recoloring, got {:?}", other),
+        }
+
+        assert_eq!(equiv("R U R' U'", "R U2 R'"), Equivalence::Different);
+    }
+}
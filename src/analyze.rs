@@ -0,0 +1,159 @@
+//! Everything we can say about a single state, all in one place
+
+use serde::Serialize;
+
+use crate::cases::recognize;
+use crate::cube::Cube;
+use crate::dist_table::{rank_cube, DistanceTable};
+use crate::orr_solve::optimal_solve_orientation;
+use crate::pos_solve::optimal_solve_position;
+
+/// Whether one face of the cube is solved, and whether the whole layer behind it is
+#[derive(Serialize, Debug, Eq, PartialEq)]
+pub struct FaceReport {
+    pub face: char,
+    pub face_solved: bool,
+    pub layer_solved: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Analysis {
+    pub input: String,
+    /// The facelet string of the state
+    pub state: String,
+    /// Where each cubie is, relative to DLB, indexed by [crate::cube::CubeletPos::index]
+    pub positions: [usize; 8],
+    /// How each cubie is twisted, relative to DLB, indexed by [crate::cube::CubeletPos::index]
+    pub orientations: [i32; 8],
+    pub orientation_distance: usize,
+    pub position_distance: usize,
+    pub distance: usize,
+    pub faces: Vec<FaceReport>,
+    pub case: Option<&'static str>,
+    pub optimal_solutions: u64,
+}
+
+pub fn analyze(input: &str, cube: &Cube, table: &DistanceTable) -> Analysis {
+    let pos = cube.clone().make_pos_arr_from_dlb();
+    let orr = cube.clone().make_orr_arr_from_dlb();
+
+    Analysis {
+        input: input.to_string(),
+        state: cube.to_facelet_string(),
+        positions: pos.to_indices(),
+        orientations: orr.to_ints(),
+        orientation_distance: optimal_solve_orientation(orr).len(),
+        position_distance: optimal_solve_position(pos).len(),
+        distance: table.distance(cube),
+        faces: face_reports(cube),
+        case: recognize(cube),
+        optimal_solutions: table.count_optimal_solutions(rank_cube(cube)),
+    }
+}
+
+/// A layer is solved when its face is, and the two stickers of the layer on each side match
+pub fn face_reports(cube: &Cube) -> Vec<FaceReport> {
+    let c = cube;
+
+    let report = |face, face_solved: bool, sides: [bool; 4]| FaceReport {
+        face,
+        face_solved,
+        layer_solved: face_solved && sides.iter().all(|&side| side),
+    };
+
+    vec![
+        report(
+            'U',
+            c.u.solved(),
+            [
+                c.f.ul == c.f.ur,
+                c.r.uf == c.r.ub,
+                c.b.ul == c.b.ur,
+                c.l.uf == c.l.ub,
+            ],
+        ),
+        report(
+            'R',
+            c.r.solved(),
+            [
+                c.u.fr == c.u.br,
+                c.f.ur == c.f.dr,
+                c.d.fr == c.d.br,
+                c.b.ur == c.b.dr,
+            ],
+        ),
+        report(
+            'F',
+            c.f.solved(),
+            [
+                c.u.fl == c.u.fr,
+                c.r.uf == c.r.df,
+                c.d.fl == c.d.fr,
+                c.l.uf == c.l.df,
+            ],
+        ),
+        report(
+            'D',
+            c.d.solved(),
+            [
+                c.f.dl == c.f.dr,
+                c.r.df == c.r.db,
+                c.b.dl == c.b.dr,
+                c.l.df == c.l.db,
+            ],
+        ),
+        report(
+            'L',
+            c.l.solved(),
+            [
+                c.u.fl == c.u.bl,
+                c.f.ul == c.f.dl,
+                c.d.fl == c.d.bl,
+                c.b.ul == c.b.dl,
+            ],
+        ),
+        report(
+            'B',
+            c.b.solved(),
+            [
+                c.u.bl == c.u.br,
+                c.r.ub == c.r.db,
+                c.d.bl == c.d.br,
+                c.l.ub == c.l.db,
+            ],
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod analyze_tests {
+    use crate::color_scheme::ColorScheme;
+    use crate::moves::{flipped_full, CanFullMove};
+    use crate::setup::parse_line;
+
+    use super::*;
+
+    fn solved_layers(cube: &Cube) -> String {
+        face_reports(cube)
+            .into_iter()
+            .filter(|r| r.layer_solved)
+            .map(|r| r.face)
+            .collect()
+    }
+
+    #[test]
+    fn layers_and_faces() {
+        let solved = ColorScheme::western().solved_cube();
+        assert_eq!(solved_layers(&solved), "URFDLB");
+
+        // an R turn leaves the R and L layers alone, but breaks up every other face
+        let r = solved.clone().apply_many_full(&parse_line("R").unwrap());
+        assert_eq!(solved_layers(&r), "RL");
+
+        // sune orients nothing on top, so the D layer is the only one left
+        let sune = solved.apply_many_full(&flipped_full(&parse_line("R U R' U R U2 R'").unwrap()));
+        let reports = face_reports(&sune);
+        assert_eq!(solved_layers(&sune), "D");
+        assert!(!reports[0].face_solved);
+    }
+}
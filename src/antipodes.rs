@@ -0,0 +1,80 @@
+//! The antipodes: the states as far from solved as it gets, which is where the length bound comes
+//! from in the first place.
+
+use crate::color_scheme::ColorScheme;
+use crate::dist_table::{rank_cube, DistanceTable};
+use crate::moves::Move;
+use crate::symmetry::all_symmetries;
+
+pub struct Antipode {
+    pub solution: Vec<Move>,
+    /// How many states are symmetric to this one, itself included
+    pub class_size: usize,
+}
+
+/// Every state at the table's maximum distance, lowest rank first; when `reduce` is set, just the
+/// lowest ranked state of each class of symmetric states
+pub fn antipodes(table: &DistanceTable, reduce: bool) -> Vec<Antipode> {
+    let symmetries = all_symmetries();
+    let states = table.states_at_distance(table.max_distance());
+
+    let mut seen = vec![false; states.len()];
+    let mut out = Vec::new();
+
+    for (i, &state) in states.iter().enumerate() {
+        if reduce && seen[i] {
+            continue;
+        }
+
+        let cube = table.cube_for_state(state, &ColorScheme::western());
+        let mut class: Vec<usize> = symmetries
+            .iter()
+            .map(|s| rank_cube(&s.apply(&cube)))
+            .collect();
+        class.sort();
+        class.dedup();
+
+        for image in &class {
+            // symmetric states are just as far away, so they're all in the list
+            let j = states.binary_search(image).unwrap();
+            seen[j] = true;
+        }
+
+        out.push(Antipode {
+            solution: table.solve_state(state),
+            class_size: class.len(),
+        });
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod antipodes_tests {
+    use crate::color_scheme::ColorScheme;
+    use crate::moves::{flipped, CanMove};
+
+    use super::*;
+
+    #[test]
+    fn hardest_states() {
+        let table = DistanceTable::build();
+
+        let all = antipodes(&table, false);
+        let reduced = antipodes(&table, true);
+
+        assert_eq!(all.len(), 2644);
+        assert_eq!(reduced.len(), 77);
+
+        // the classes cover every antipode exactly once
+        let covered: usize = reduced.iter().map(|a| a.class_size).sum();
+        assert_eq!(covered, all.len());
+
+        let solved = ColorScheme::western().solved_cube();
+        for antipode in &reduced {
+            let cube = solved.clone().apply_many(&flipped(&antipode.solution));
+            assert_eq!(table.distance(&cube), 11);
+            assert!(cube.apply_many(&antipode.solution).solved());
+        }
+    }
+}
@@ -0,0 +1,412 @@
+//! Blindfolded solving with Old Pochmann: the corner in the buffer is swapped, one facelet at a
+//! time, into where it belongs, which brings a new corner into the buffer. Each swap is a setup
+//! taking the target facelet to a fixed helper facelet (without touching the buffer), one swap
+//! algorithm exchanging the buffer and helper corners, and the setup undone.
+//!
+//! Everything is worked out relative to the DLB corner, the same as the solvers, from the position
+//! and orientation arrangements of the cube.
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::cube::{corner_turn, Cube, CubeletOrientation, CubeletPos, CORNER_FACELETS};
+use crate::masked::facelet_sources_full;
+use crate::moves::{cancelled_full, flipped_full, Amt, CanFullMove, FullDir, FullMove};
+use crate::permutation::position_cycles;
+use crate::setup::parse_line;
+
+/// Exchanges the ULB and DRF corners, taking the U facelet of one to the R facelet of the other;
+/// the usual Old Pochmann swap (a Y permutation which leaves the U layer alone)
+const SWAP_ALG: &str = "R U' R' U' R U R' F' R U R' U' R' F R";
+
+/// The facelets in Speffz order: each of the U, L, F, R, B and D faces in turn, clockwise from the
+/// top left corner as you look at the face (with U seen from above with B at the top, and D from
+/// below with F at the top)
+const SPEFFZ_ORDER: [usize; 24] = [
+    0, 1, 3, 2, 16, 17, 19, 18, 8, 9, 11, 10, 4, 5, 7, 6, 20, 21, 23, 22, 12, 13, 15, 14,
+];
+
+const ALL_FULL_MOVES: [FullMove; 18] = {
+    use Amt::*;
+    use FullDir::*;
+
+    [
+        FullMove(R, One),
+        FullMove(R, Two),
+        FullMove(R, Rev),
+        FullMove(L, One),
+        FullMove(L, Two),
+        FullMove(L, Rev),
+        FullMove(F, One),
+        FullMove(F, Two),
+        FullMove(F, Rev),
+        FullMove(B, One),
+        FullMove(B, Two),
+        FullMove(B, Rev),
+        FullMove(U, One),
+        FullMove(U, Two),
+        FullMove(U, Rev),
+        FullMove(D, One),
+        FullMove(D, Two),
+        FullMove(D, Rev),
+    ]
+};
+
+/// A letter for every facelet, for memorizing targets
+#[derive(Clone, Debug)]
+pub struct LetterScheme {
+    /// Indexed like [Cube::to_facelets]
+    letters: [char; 24],
+}
+
+impl LetterScheme {
+    pub fn speffz() -> Self {
+        Self::parse("ABCDEFGHIJKLMNOPQRSTUVWX").unwrap()
+    }
+
+    /// Reads 24 distinct letters, given in Speffz order (so the Speffz scheme itself is just the
+    /// alphabet); whitespace is ignored
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let given: Vec<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+
+        if given.len() != 24 {
+            return Err(format!("Expected 24 letters, got {}", given.len()));
+        }
+
+        let distinct: HashSet<char> = given.iter().copied().collect();
+        if distinct.len() != 24 {
+            return Err("Every facelet needs its own letter".to_string());
+        }
+
+        let mut letters = [' '; 24];
+        for (&facelet, &letter) in SPEFFZ_ORDER.iter().zip(given.iter()) {
+            letters[facelet] = letter;
+        }
+
+        Ok(LetterScheme { letters })
+    }
+
+    pub fn letter(&self, facelet: usize) -> char {
+        self.letters[facelet]
+    }
+}
+
+/// Reads a corner name like ULB, in any order of faces and any case
+pub fn parse_corner(name: &str) -> Option<CubeletPos> {
+    let mut faces: Vec<char> = name.to_uppercase().chars().collect();
+    faces.sort();
+
+    (0..8).map(CubeletPos::from_index).find(|pos| {
+        let mut want: Vec<char> = format!("{:?}", pos).chars().collect();
+        want.sort();
+        want == faces
+    })
+}
+
+/// One target of the memo, and the moves which shoot the buffer there
+pub struct Target {
+    pub letter: char,
+    /// Whether the buffer held its own corner, so this just moves it out of the way to start a new
+    /// cycle
+    pub cycle_break: bool,
+    pub moves: Vec<FullMove>,
+}
+
+pub struct BlindSolve {
+    /// The cycles of positions the corners need to move around (relative to DLB), not counting
+    /// corners already in place; each corner goes to the next position in its cycle
+    pub cycles: Vec<Vec<CubeletPos>>,
+    /// Corners (other than the buffer) in the right place but twisted, and which way
+    pub twisted: Vec<(CubeletPos, CubeletOrientation)>,
+    pub targets: Vec<Target>,
+    /// Every target's moves, one after another (with the turns between them merged)
+    pub execution: Vec<FullMove>,
+}
+
+impl BlindSolve {
+    /// The targets' letters, in pairs
+    pub fn memo(&self) -> String {
+        let letters: Vec<char> = self.targets.iter().map(|t| t.letter).collect();
+
+        letters
+            .chunks(2)
+            .map(|pair| pair.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Old Pochmann for a particular buffer; the setups only depend on the buffer, so this works them
+/// all out up front
+pub struct OldPochmann {
+    buffer: CubeletPos,
+    /// Exchanges the buffer and helper corners
+    swap: Vec<FullMove>,
+    helper: usize,
+    /// For each facelet, moves taking it to the helper facelet without touching the buffer; None
+    /// for the buffer's own facelets
+    setups: [Option<Vec<FullMove>>; 24],
+}
+
+/// Where the facelet at `facelet` ends up after the moves with these sources
+fn destination(sources: &[usize; 24], facelet: usize) -> usize {
+    sources.iter().position(|&s| s == facelet).unwrap()
+}
+
+/// The shortest sequence of `moves` taking the facelet at `from` to somewhere in `to`
+fn shortest_route(from: usize, to: &[usize], moves: &[FullMove]) -> Option<Vec<FullMove>> {
+    let move_sources: Vec<[usize; 24]> =
+        moves.iter().map(|&m| facelet_sources_full(&[m])).collect();
+
+    let mut seen = [false; 24];
+    seen[from] = true;
+    let mut queue = VecDeque::from([(from, Vec::new())]);
+
+    while let Some((here, route)) = queue.pop_front() {
+        if to.contains(&here) {
+            return Some(route);
+        }
+
+        for (m, sources) in moves.iter().zip(move_sources.iter()) {
+            let next = destination(sources, here);
+            if !seen[next] {
+                seen[next] = true;
+                let mut next_route = route.clone();
+                next_route.push(*m);
+                queue.push_back((next, next_route));
+            }
+        }
+    }
+
+    None
+}
+
+impl OldPochmann {
+    pub fn new(buffer: CubeletPos) -> Self {
+        let buffer_facelets = CORNER_FACELETS[buffer.index()];
+        let buffer_ref = buffer_facelets[0];
+
+        // turn the whole problem so the usual swap reaches the buffer
+        let base = parse_line(SWAP_ALG).unwrap();
+        let base_sources = facelet_sources_full(&base);
+        let swapped: Vec<usize> = (0..24).filter(|&i| base_sources[i] != i).collect();
+        let conjugate = shortest_route(buffer_ref, &swapped, &ALL_FULL_MOVES)
+            .expect("Every facelet can reach every other");
+
+        let swap = cancelled_full(&[conjugate.clone(), base, flipped_full(&conjugate)].concat());
+        let helper = facelet_sources_full(&swap)[buffer_ref];
+
+        let allowed: Vec<FullMove> = ALL_FULL_MOVES
+            .into_iter()
+            .filter(|&m| {
+                let sources = facelet_sources_full(&[m]);
+                buffer_facelets.iter().all(|&i| sources[i] == i)
+            })
+            .collect();
+
+        let setups = std::array::from_fn(|facelet| {
+            if buffer_facelets.contains(&facelet) {
+                None
+            } else {
+                Some(
+                    shortest_route(facelet, &[helper], &allowed)
+                        .expect("The helper can be reached without touching the buffer"),
+                )
+            }
+        });
+
+        OldPochmann {
+            buffer,
+            swap,
+            helper,
+            setups,
+        }
+    }
+
+    pub fn swap(&self) -> &[FullMove] {
+        &self.swap
+    }
+
+    pub fn helper(&self) -> usize {
+        self.helper
+    }
+
+    /// Setup, swap, and undo the setup: shoots the buffer's facelet to `facelet`
+    fn shoot(&self, facelet: usize) -> Vec<FullMove> {
+        let setup = self.setups[facelet].as_ref().unwrap();
+
+        cancelled_full(&[setup.clone(), self.swap.clone(), flipped_full(setup)].concat())
+    }
+
+    pub fn solve(&self, cube: &Cube, letters: &LetterScheme) -> BlindSolve {
+        let positions = cube.clone().make_pos_arr_from_dlb().to_indices();
+        let orientations = cube.clone().make_orr_arr_from_dlb().to_ints();
+
+        // home[i] is where the facelet now at i needs to go
+        let mut home = [0; 24];
+        for (pos, (&cubie, &twist)) in positions.iter().zip(orientations.iter()).enumerate() {
+            let turn = corner_turn(pos, cubie, &CubeletOrientation::from_int(twist));
+            for k in 0..3 {
+                home[CORNER_FACELETS[pos][k]] = CORNER_FACELETS[cubie][(k + turn) % 3];
+            }
+        }
+
+        let buffer_facelets = CORNER_FACELETS[self.buffer.index()];
+        let corner_solved =
+            |home: &[usize; 24], pos: usize| CORNER_FACELETS[pos].iter().all(|&i| home[i] == i);
+
+        let mut targets = Vec::new();
+
+        loop {
+            let (facelet, cycle_break) = if buffer_facelets.contains(&home[buffer_facelets[0]]) {
+                let next = SPEFFZ_ORDER.into_iter().find(|&i| {
+                    let pos = CORNER_FACELETS.iter().position(|c| c.contains(&i)).unwrap();
+                    CORNER_FACELETS[pos][0] == i
+                        && pos != self.buffer.index()
+                        && !corner_solved(&home, pos)
+                });
+
+                match next {
+                    Some(facelet) => (facelet, true),
+                    None => break,
+                }
+            } else {
+                (home[buffer_facelets[0]], false)
+            };
+
+            let moves = self.shoot(facelet);
+            let sources = facelet_sources_full(&moves);
+            home = sources.map(|s| home[s]);
+
+            targets.push(Target {
+                letter: letters.letter(facelet),
+                cycle_break,
+                moves,
+            });
+
+            assert!(
+                targets.len() <= 24,
+                "Old Pochmann should not need this many targets"
+            );
+        }
+
+        BlindSolve {
+            cycles: position_cycles(&positions),
+            twisted: (0..8)
+                .filter(|&pos| {
+                    pos != self.buffer.index() && positions[pos] == pos && orientations[pos] != 0
+                })
+                .map(|pos| {
+                    (
+                        CubeletPos::from_index(pos),
+                        CubeletOrientation::from_int(orientations[pos]),
+                    )
+                })
+                .collect(),
+            execution: cancelled_full(
+                &targets
+                    .iter()
+                    .flat_map(|t| t.moves.clone())
+                    .collect::<Vec<_>>(),
+            ),
+            targets,
+        }
+    }
+}
+
+/// Whether doing the execution solves the cube
+pub fn verify(cube: &Cube, solve: &BlindSolve) -> bool {
+    cube.clone().apply_many_full(&solve.execution).solved()
+}
+
+#[cfg(test)]
+mod blind_tests {
+    use crate::color_scheme::ColorScheme;
+    use crate::moves::nice_write;
+    use crate::scramble::{full_scramble, put_cubie};
+
+    use super::*;
+
+    fn cube(alg: &str) -> Cube {
+        ColorScheme::western()
+            .solved_cube()
+            .apply_many_full(&parse_line(alg).unwrap())
+    }
+
+    #[test]
+    fn letters_and_corners() {
+        let speffz = LetterScheme::speffz();
+        let buffer: String = CORNER_FACELETS[CubeletPos::ULB.index()]
+            .iter()
+            .map(|&i| speffz.letter(i))
+            .collect();
+        assert_eq!(buffer, "AER");
+
+        assert_eq!(parse_corner("bul"), Some(CubeletPos::ULB));
+        assert_eq!(parse_corner("UL"), None);
+        assert!(LetterScheme::parse("ABC").is_err());
+        assert!(LetterScheme::parse("AACDEFGHIJKLMNOPQRSTUVWX").is_err());
+    }
+
+    #[test]
+    fn swap_exchanges_buffer_and_helper() {
+        for buffer in [CubeletPos::ULB, CubeletPos::DRF, CubeletPos::DLB] {
+            let op = OldPochmann::new(buffer.clone());
+            let sources = facelet_sources_full(op.swap());
+
+            let moved: Vec<usize> = (0..24).filter(|&i| sources[i] != i).collect();
+            assert_eq!(moved.len(), 6);
+
+            let buffer_ref = CORNER_FACELETS[buffer.index()][0];
+            assert_eq!(sources[op.helper()], buffer_ref);
+            assert_eq!(sources[buffer_ref], op.helper());
+        }
+    }
+
+    #[test]
+    fn executions_solve() {
+        let letters = LetterScheme::speffz();
+        let op = OldPochmann::new(CubeletPos::ULB);
+
+        // a solved cube needs nothing
+        let solved = op.solve(&cube(""), &letters);
+        assert!(solved.targets.is_empty());
+        assert_eq!(solved.memo(), "");
+
+        // U swaps around a four cycle, which is three targets
+        let u = op.solve(&cube("U"), &letters);
+        assert_eq!(u.cycles.len(), 1);
+        assert_eq!(u.targets.len(), 3);
+        assert!(verify(&cube("U"), &u));
+
+        // each twisted corner takes a cycle break into it, then one more target to bring it back
+        let mut twisted = cube("");
+        let corners = twisted.corners();
+        put_cubie(&mut twisted, 6, corners[6].clone(), 1);
+        put_cubie(&mut twisted, 7, corners[7].clone(), 2);
+
+        let solve = op.solve(&twisted, &letters);
+        assert!(solve.cycles.is_empty());
+        assert_eq!(solve.twisted.len(), 2);
+        let breaks: Vec<bool> = solve.targets.iter().map(|t| t.cycle_break).collect();
+        assert_eq!(breaks, vec![true, false, true, false]);
+        assert_eq!(solve.memo(), "BN CM");
+        assert!(verify(&twisted, &solve));
+
+        for buffer in [CubeletPos::ULB, CubeletPos::URF, CubeletPos::DRB] {
+            let op = OldPochmann::new(buffer);
+            for _ in 0..20 {
+                let scrambled = full_scramble(&ColorScheme::western());
+                let solve = op.solve(&scrambled, &letters);
+                assert!(
+                    verify(&scrambled, &solve),
+                    "{} did not solve",
+                    nice_write(&solve.execution)
+                );
+            }
+        }
+
+        // the moves work on any cube, whole-cube turns and all
+        let turned = cube("R L' U2");
+        assert!(verify(&turned, &op.solve(&turned, &letters)));
+    }
+}
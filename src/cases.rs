@@ -0,0 +1,115 @@
+//! Named last-layer cases, so we can tell someone "that's a Sune" instead of just giving moves
+
+use crate::cube::Cube;
+use crate::moves::{rotated_full, u_turns, Amt, Axis, CanFullMove, Rotation};
+use crate::setup::parse_line;
+
+/// A last-layer case, given by an algorithm which solves it (with the D layer solved)
+pub struct Case {
+    pub name: &'static str,
+    pub alg: &'static str,
+}
+
+pub const CASES: [Case; 9] = [
+    Case {
+        name: "Sune",
+        alg: "R U R' U R U2 R'",
+    },
+    Case {
+        name: "Antisune",
+        alg: "R U2 R' U' R U' R'",
+    },
+    Case {
+        name: "H",
+        alg: "R2 U2 R U2 R2",
+    },
+    Case {
+        name: "Pi",
+        alg: "R U2 R2 U' R2 U' R2 U2 R",
+    },
+    Case {
+        name: "U",
+        alg: "F R U R' U' F'",
+    },
+    Case {
+        name: "T",
+        alg: "R U R' U' R' F R F'",
+    },
+    Case {
+        name: "L",
+        alg: "F R' F' R U R U' R'",
+    },
+    Case {
+        name: "Adjacent swap",
+        alg: "R U R' U' R' F R2 U' R' U' R U R' F'",
+    },
+    Case {
+        name: "Diagonal swap",
+        alg: "F R U' R' U' R U R' F' R U R' U' R' F R F'",
+    },
+];
+
+/// The name of the case the cube is in, if it's one of [CASES] up to U turns before and after,
+/// and which side of the cube it's held from. Solved cubes (and ones which only need a U turn)
+/// aren't a case.
+pub fn recognize(cube: &Cube) -> Option<&'static str> {
+    if (0..4).any(|auf| cube.clone().apply_many_full(&u_turns(auf)).solved()) {
+        return None;
+    }
+
+    for case in CASES {
+        let mut alg = parse_line(case.alg).expect("Case algs should all parse");
+
+        for _ in 0..4 {
+            for pre in 0..4 {
+                let setup = cube.clone().apply_many_full(&u_turns(pre));
+                let after = setup.apply_many_full(&alg);
+
+                if (0..4).any(|post| after.clone().apply_many_full(&u_turns(post)).solved()) {
+                    return Some(case.name);
+                }
+            }
+
+            alg = rotated_full(&alg, &[Rotation(Axis::Y, Amt::One)]);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod cases_tests {
+    use crate::color_scheme::ColorScheme;
+    use crate::moves::flipped_full;
+
+    use super::*;
+
+    fn case_state(alg: &str) -> Cube {
+        let moves = parse_line(alg).unwrap();
+        ColorScheme::western()
+            .solved_cube()
+            .apply_many_full(&flipped_full(&moves))
+    }
+
+    #[test]
+    fn every_case_recognizes_itself() {
+        for case in CASES {
+            let cube = case_state(case.alg);
+
+            // every case is last layer only
+            assert!(cube.d.solved(), "{} should leave D solved", case.name);
+            assert_eq!(recognize(&cube), Some(case.name));
+        }
+    }
+
+    #[test]
+    fn recognize_from_other_angles() {
+        // a sune from the back, with a U turn on either side
+        assert_eq!(
+            recognize(&case_state("U L U L' U L U2 L' U2")),
+            Some("Sune")
+        );
+        assert_eq!(recognize(&case_state("U'")), None);
+        assert_eq!(recognize(&case_state("R U")), None);
+    }
+}
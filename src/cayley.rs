@@ -0,0 +1,249 @@
+//! The Cayley graph of a set of moves: a node for every state, and an edge for every move
+//! between two of them. The whole thing is far too big to look at, so this is only ever a small
+//! subgroup, or the states within a few moves of solved, written out as DOT (for graphviz) or
+//! GraphML (for most anything else).
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use clap::ValueEnum;
+
+use crate::cube::Cube;
+use crate::dist_table::rank_cube;
+use crate::full_solve::{ShortCircuitCache, SimpleShortCircuitCache};
+use crate::moves::{Amt, CanMove, Move, MoveSet};
+
+/// More than this and nothing is going to draw it usefully anyway
+pub const MAX_NODES: usize = 50_000;
+
+#[derive(ValueEnum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum GraphFormat {
+    Dot,
+    #[value(name = "graphml")]
+    GraphMl,
+}
+
+/// What to call each node
+#[derive(ValueEnum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum NodeLabel {
+    /// The 24 facelets, face by face in the order U R F D L B
+    Facelets,
+    /// The rank of the state, as in the distance table
+    Rank,
+}
+
+pub struct Node {
+    pub cube: Cube,
+    pub distance: usize,
+}
+
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub m: Move,
+}
+
+pub struct CayleyGraph {
+    pub moves: MoveSet,
+    /// In order of distance from solved
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+impl CayleyGraph {
+    /// Every state within `depth` moves (from the set) of solved, and the moves between them.
+    /// Starting from a depth at least the diameter of the subgroup gets the whole subgroup.
+    pub fn around(solved: Cube, moves: MoveSet, depth: usize) -> Self {
+        // the short circuit cache walk already finds every state within a depth, and how far away
+        // each one is
+        let mut ball = SimpleShortCircuitCache::default();
+        ball.load_with_depth(depth, solved, &moves);
+
+        let mut nodes: Vec<(usize, Node)> = ball
+            .states()
+            .map(|(cube, distance)| {
+                let node = Node {
+                    cube: cube.clone(),
+                    distance,
+                };
+                (rank_cube(cube), node)
+            })
+            .collect();
+        nodes.sort_by_key(|(rank, node)| (node.distance, *rank));
+
+        let index: HashMap<usize, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, (rank, _))| (*rank, i))
+            .collect();
+
+        // each edge once: going back along a quarter turn is the opposite quarter turn, and a half
+        // turn is its own way back
+        let mut edges = Vec::new();
+        for (from, (_, node)) in nodes.iter().enumerate() {
+            for m in moves.moves().filter(|m| m.amt != Amt::Rev) {
+                let Some(&to) = index.get(&rank_cube(&node.cube.clone().apply(m))) else {
+                    continue;
+                };
+
+                if m.amt == Amt::Two && to < from {
+                    continue;
+                }
+
+                edges.push(Edge { from, to, m });
+            }
+        }
+
+        CayleyGraph {
+            moves,
+            nodes: nodes.into_iter().map(|(_, node)| node).collect(),
+            edges,
+        }
+    }
+
+    fn label(&self, node: &Node, label: NodeLabel) -> String {
+        match label {
+            NodeLabel::Facelets => node.cube.to_facelet_string(),
+            NodeLabel::Rank => rank_cube(&node.cube).to_string(),
+        }
+    }
+
+    pub fn to_dot(&self, label: NodeLabel) -> String {
+        let mut out = String::new();
+
+        writeln!(out, "digraph \"{}\" {{", self.moves).unwrap();
+        for (i, node) in self.nodes.iter().enumerate() {
+            writeln!(
+                out,
+                "  n{} [label=\"{}\\n{}\", distance={}];",
+                i,
+                self.label(node, label),
+                node.distance,
+                node.distance
+            )
+            .unwrap();
+        }
+        for edge in &self.edges {
+            writeln!(
+                out,
+                "  n{} -> n{} [label=\"{}\"];",
+                edge.from, edge.to, edge.m
+            )
+            .unwrap();
+        }
+        out.push_str("}\n");
+
+        out
+    }
+
+    pub fn to_graphml(&self, label: NodeLabel) -> String {
+        let mut out = String::new();
+
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        out.push_str(
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+        );
+        out.push_str(
+            "  <key id=\"distance\" for=\"node\" attr.name=\"distance\" attr.type=\"int\"/>\n",
+        );
+        out.push_str("  <key id=\"move\" for=\"edge\" attr.name=\"move\" attr.type=\"string\"/>\n");
+
+        // the move set's brackets have to be escaped
+        let name = self
+            .moves
+            .to_string()
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        writeln!(out, "  <graph id=\"{}\" edgedefault=\"directed\">", name).unwrap();
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            writeln!(out, "    <node id=\"n{}\">", i).unwrap();
+            writeln!(
+                out,
+                "      <data key=\"label\">{}</data>",
+                self.label(node, label)
+            )
+            .unwrap();
+            writeln!(out, "      <data key=\"distance\">{}</data>", node.distance).unwrap();
+            out.push_str("    </node>\n");
+        }
+
+        for edge in &self.edges {
+            writeln!(
+                out,
+                "    <edge source=\"n{}\" target=\"n{}\"><data key=\"move\">{}</data></edge>",
+                edge.from, edge.to, edge.m
+            )
+            .unwrap();
+        }
+
+        out.push_str("  </graph>\n</graphml>\n");
+
+        out
+    }
+
+    pub fn write(&self, format: GraphFormat, label: NodeLabel) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(label),
+            GraphFormat::GraphMl => self.to_graphml(label),
+        }
+    }
+}
+
+#[cfg(test)]
+mod cayley_tests {
+    use crate::color_scheme::ColorScheme;
+
+    use super::*;
+
+    fn graph(moves: &str, depth: usize) -> CayleyGraph {
+        let solved = ColorScheme::western().solved_cube();
+        CayleyGraph::around(solved, moves.parse().unwrap(), depth)
+    }
+
+    #[test]
+    fn whole_subgroup() {
+        // the half turns of three faces make 24 states, all within 4 moves
+        let g = graph("<R2,U2,F2>", 4);
+        assert_eq!(g.nodes.len(), 24);
+        assert_eq!(g.nodes[0].distance, 0);
+        assert!(g.nodes.windows(2).all(|w| w[0].distance <= w[1].distance));
+
+        // three half turns out of every state, each edge counted from one end
+        assert_eq!(g.edges.len(), 24 * 3 / 2);
+
+        for edge in &g.edges {
+            let moved = g.nodes[edge.from].cube.clone().apply(edge.m);
+            assert_eq!(rank_cube(&moved), rank_cube(&g.nodes[edge.to].cube));
+        }
+    }
+
+    #[test]
+    fn ball_around_solved() {
+        let g = graph("<R,U>", 1);
+        assert_eq!(g.nodes.len(), 7);
+
+        // from solved, one edge each for R, R2, U and U2; R' and U' come in the other way
+        assert!(g
+            .edges
+            .iter()
+            .all(|e| e.from < g.nodes.len() && e.to < g.nodes.len()));
+        assert_eq!(g.edges.iter().filter(|e| e.from == 0).count(), 4);
+    }
+
+    #[test]
+    fn formats() {
+        let g = graph("<U>", 1);
+
+        let dot = g.write(GraphFormat::Dot, NodeLabel::Rank);
+        assert!(dot.starts_with("digraph \"<U>\" {"));
+        assert!(dot.contains("n0 -> n"));
+        assert!(dot.contains("[label=\"U2\"]"));
+
+        let graphml = g.write(GraphFormat::GraphMl, NodeLabel::Facelets);
+        assert!(graphml.contains("<graph id=\"&lt;U&gt;\""));
+        assert_eq!(graphml.matches("<node ").count(), 4);
+        assert!(graphml.contains(&ColorScheme::western().solved_cube().to_facelet_string()));
+    }
+}
@@ -0,0 +1,214 @@
+//! Which colors go where on a solved cube. Everything else in the crate only cares about which
+//! facelets are equal to which, but building a solved cube, or checking that some stickers
+//! actually make up a real cube, needs to know the opposite pairs and which way around the
+//! corners go.
+
+use std::str::FromStr;
+
+use crate::cube::{Cube, Facelet, ALL_FACELETS};
+
+/// A color scheme, given by the face colors of a solved cube held in some reference orientation.
+/// U/D, R/L and F/B are the opposite pairs, and going clockwise around the URF corner gives
+/// U, R, F; that pins down the chirality of every corner.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ColorScheme {
+    pub u: Facelet,
+    pub r: Facelet,
+    pub f: Facelet,
+    pub d: Facelet,
+    pub l: Facelet,
+    pub b: Facelet,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        Self::western()
+    }
+}
+
+impl ColorScheme {
+    /// White opposite yellow, blue opposite green, red opposite orange; the scheme of
+    /// [crate::cube::ALL_CUBIES]
+    pub fn western() -> Self {
+        ColorScheme {
+            u: Facelet::White,
+            r: Facelet::Red,
+            f: Facelet::Green,
+            d: Facelet::Yellow,
+            l: Facelet::Orange,
+            b: Facelet::Blue,
+        }
+    }
+
+    /// White opposite blue, yellow opposite green, red opposite orange
+    pub fn japanese() -> Self {
+        ColorScheme {
+            u: Facelet::White,
+            r: Facelet::Red,
+            f: Facelet::Green,
+            d: Facelet::Blue,
+            l: Facelet::Orange,
+            b: Facelet::Yellow,
+        }
+    }
+
+    /// Builds a scheme from the colors of the U, R, F, D, L and B faces (in that order), which
+    /// must all be different
+    pub fn from_faces(faces: [Facelet; 6]) -> Result<Self, [Facelet; 6]> {
+        if ALL_FACELETS.iter().any(|c| !faces.contains(c)) {
+            return Err(faces);
+        }
+
+        let [u, r, f, d, l, b] = faces;
+
+        Ok(ColorScheme { u, r, f, d, l, b })
+    }
+
+    pub fn opposite(&self, color: &Facelet) -> Facelet {
+        let pairs = [(&self.u, &self.d), (&self.r, &self.l), (&self.f, &self.b)];
+
+        for (a, b) in pairs {
+            if color == a {
+                return b.clone();
+            } else if color == b {
+                return a.clone();
+            }
+        }
+
+        unreachable!("Every color is in the scheme, so it should have an opposite")
+    }
+
+    /// Every corner cubie of this scheme, in clockwise order starting from the U or D facelet,
+    /// indexed by [crate::cube::CubeletPos::index] of where it sits on a solved cube
+    pub fn corners(&self) -> [[Facelet; 3]; 8] {
+        self.solved_cube().corners()
+    }
+
+    /// Returns the "next" color for a (corner) cubie, in clockwise order, starting from a, to b,
+    /// to (the return value).
+    pub fn next_color(&self, a: &Facelet, b: &Facelet) -> Facelet {
+        if self.opposite(a) == *b || a == b {
+            panic!(
+                "The colors {:?} and {:?} do not appear on a corner together",
+                a, b
+            )
+        }
+
+        for cubie in self.corners() {
+            for i in 0..3 {
+                if *a == cubie[i] {
+                    let next = (i + 1) % 3;
+                    if *b == cubie[next] {
+                        let last = (next + 1) % 3;
+                        return cubie[last].clone();
+                    }
+                }
+            }
+        }
+
+        unreachable!("Given the facelets {:?} and {:?} which are not equal or opposites, we should have found a third facelet, but didn't", a, b)
+    }
+
+    /// Whether the three facelets (in clockwise order) make up one of the corners of this scheme
+    pub fn is_corner(&self, cubie: &[Facelet; 3]) -> bool {
+        self.corners()
+            .iter()
+            .any(|corner| same_cubie(corner, cubie))
+    }
+
+    /// The solved cube in the reference orientation of this scheme
+    pub fn solved_cube(&self) -> Cube {
+        Cube::from_faces(
+            self.u.clone(),
+            self.d.clone(),
+            self.r.clone(),
+            self.l.clone(),
+            self.f.clone(),
+            self.b.clone(),
+        )
+    }
+}
+
+/// Whether two corners (given in clockwise order) are the same cubie, possibly rotated
+pub fn same_cubie(a: &[Facelet; 3], b: &[Facelet; 3]) -> bool {
+    (0..3).any(|offset| (0..3).all(|i| a[i] == b[(i + offset) % 3]))
+}
+
+impl FromStr for ColorScheme {
+    type Err = String;
+
+    /// Accepts "western", "japanese", or six color letters for the U, R, F, D, L and B faces, such
+    /// as "WRGYOB" (which is the western scheme)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "western" => return Ok(ColorScheme::western()),
+            "japanese" => return Ok(ColorScheme::japanese()),
+            _ => {}
+        }
+
+        let colors: Vec<Facelet> = s
+            .chars()
+            .map(Facelet::try_from)
+            .collect::<Result<_, _>>()
+            .map_err(|c| format!("Unknown color {:?}", c))?;
+
+        let faces: [Facelet; 6] = colors
+            .try_into()
+            .map_err(|_| format!("Expected six colors, got {:?}", s))?;
+
+        ColorScheme::from_faces(faces).map_err(|_| format!("Colors must all differ: {:?}", s))
+    }
+}
+
+#[cfg(test)]
+mod color_scheme_tests {
+    use crate::cube::{CubeletPos, ALL_CUBIES};
+
+    use super::*;
+
+    #[test]
+    fn western_matches_all_cubies() {
+        let scheme = ColorScheme::western();
+
+        for cubie in ALL_CUBIES {
+            assert!(scheme.is_corner(&cubie));
+        }
+
+        assert_eq!(
+            scheme.solved_cube(),
+            Cube::make_solved(Facelet::Green, Facelet::White)
+        );
+    }
+
+    #[test]
+    fn japanese_opposites() {
+        let scheme = ColorScheme::japanese();
+
+        assert_eq!(scheme.opposite(&Facelet::White), Facelet::Blue);
+        assert_eq!(scheme.opposite(&Facelet::Green), Facelet::Yellow);
+        assert_eq!(scheme.opposite(&Facelet::Red), Facelet::Orange);
+
+        for c in ALL_FACELETS {
+            assert_eq!(scheme.opposite(&scheme.opposite(&c)), c);
+        }
+
+        // the solved cube should know its own opposites
+        let cube = scheme.solved_cube();
+        assert_eq!(cube.opposite_color(&Facelet::Yellow), Facelet::Green);
+        assert_eq!(
+            cube.corners()[CubeletPos::URF.index()],
+            [Facelet::White, Facelet::Red, Facelet::Green]
+        );
+    }
+
+    #[test]
+    fn parse_schemes() {
+        assert_eq!("western".parse(), Ok(ColorScheme::western()));
+        assert_eq!("Japanese".parse(), Ok(ColorScheme::japanese()));
+        assert_eq!("WRGYOB".parse(), Ok(ColorScheme::western()));
+
+        assert!("WRGYO".parse::<ColorScheme>().is_err());
+        assert!("WRGYOO".parse::<ColorScheme>().is_err());
+        assert!("WRGYOX".parse::<ColorScheme>().is_err());
+    }
+}
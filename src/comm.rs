@@ -0,0 +1,375 @@
+//! Finding commutators `[A, B]` (A, then B, then A undone, then B undone) and conjugates
+//! `[S: [A, B]]` (a setup, then a commutator, then the setup undone) with a given effect on the
+//! corners, like cycling three of them or twisting two in place.
+//!
+//! Everything is worked out on which facelet goes where (see [crate::masked::facelet_sources]),
+//! with R, U and F moves, so DLB never moves and can't be part of the effect.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use crate::color_scheme::ColorScheme;
+use crate::cube::{CubeletPos, CORNER_FACELETS};
+use crate::masked::facelet_sources;
+use crate::moves::{cancelled_full, flipped, nice_write, CanMove, FullMove, Move, ALL_MOVES};
+
+/// Where each facelet came from, as in [crate::masked::facelet_sources]
+type Sources = [u8; 24];
+
+/// What the commutator should do; every other corner has to stay exactly where it is
+#[derive(Clone, Debug)]
+pub enum Effect {
+    /// The first corner goes to the second, the second to the third, and the third to the first,
+    /// each keeping its U or D facelet on the U or D face
+    Cycle([CubeletPos; 3]),
+    /// The first corner twists clockwise, and the second anticlockwise (as you look at each)
+    Twist([CubeletPos; 2]),
+}
+
+impl Effect {
+    fn corners(&self) -> Vec<CubeletPos> {
+        match self {
+            Effect::Cycle(corners) => corners.to_vec(),
+            Effect::Twist(corners) => corners.to_vec(),
+        }
+    }
+
+    /// What's wrong with asking for this, if anything
+    pub fn check(&self) -> Result<(), String> {
+        let corners = self.corners();
+
+        if corners.contains(&CubeletPos::DLB) {
+            return Err("DLB is held still, so it can't be part of the effect".to_string());
+        }
+
+        for (i, corner) in corners.iter().enumerate() {
+            if corners[i + 1..].contains(corner) {
+                return Err(format!("{:?} is in the effect twice", corner));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Where each facelet comes from, once the effect is done
+    fn sources(&self) -> [usize; 24] {
+        let mut sources: [usize; 24] = std::array::from_fn(|i| i);
+
+        // every corner's U or D facelet comes first in its triple, and the other two follow
+        // clockwise, so the cubie from `from` lands in `to` with the facelet in each slot coming
+        // from `turn` slots further round
+        let mut put = |from: &CubeletPos, to: &CubeletPos, turn: usize| {
+            for k in 0..3 {
+                sources[CORNER_FACELETS[to.index()][k]] =
+                    CORNER_FACELETS[from.index()][(k + turn) % 3];
+            }
+        };
+
+        match self {
+            Effect::Cycle([a, b, c]) => {
+                put(a, b, 0);
+                put(b, c, 0);
+                put(c, a, 0);
+            }
+            Effect::Twist([a, b]) => {
+                put(a, a, 2);
+                put(b, b, 1);
+            }
+        }
+
+        sources
+    }
+}
+
+/// How long each part of the conjugate may be
+#[derive(Copy, Clone, Debug)]
+pub struct Limits {
+    pub setup: usize,
+    pub first: usize,
+    pub second: usize,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Commutator {
+    /// Empty for a plain commutator
+    pub setup: Vec<Move>,
+    pub first: Vec<Move>,
+    pub second: Vec<Move>,
+}
+
+impl Commutator {
+    /// The whole thing written out, without cancelling anything
+    pub fn moves(&self) -> Vec<Move> {
+        [
+            self.setup.clone(),
+            self.first.clone(),
+            self.second.clone(),
+            flipped(&self.first),
+            flipped(&self.second),
+            flipped(&self.setup),
+        ]
+        .concat()
+    }
+
+    /// The whole thing written out, with neighbouring turns of the same face merged
+    pub fn cancelled(&self) -> Vec<FullMove> {
+        let moves: Vec<FullMove> = self.moves().into_iter().map(FullMove::from).collect();
+        cancelled_full(&moves)
+    }
+
+    /// How many moves it is to do, once the cancellations are taken out
+    pub fn len(&self) -> usize {
+        self.cancelled().len()
+    }
+}
+
+impl Display for Commutator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let inner = format!(
+            "[{}, {}]",
+            nice_write(&self.first),
+            nice_write(&self.second)
+        );
+
+        if self.setup.is_empty() {
+            write!(f, "{}", inner)
+        } else {
+            write!(f, "[{}: {}]", nice_write(&self.setup), inner)
+        }
+    }
+}
+
+/// Doing `first` and then `second`
+fn then(first: &Sources, second: &Sources) -> Sources {
+    second.map(|s| first[s as usize])
+}
+
+fn inverse(sources: &Sources) -> Sources {
+    let mut out = [0; 24];
+    for (i, &s) in sources.iter().enumerate() {
+        out[s as usize] = i as u8;
+    }
+    out
+}
+
+/// Every sequence of R, U and F moves up to this long (never turning the same face twice in a
+/// row), with what it does, shortest first
+fn sequences(max_length: usize) -> Vec<(Vec<Move>, Sources)> {
+    let move_sources: Vec<Sources> = ALL_MOVES
+        .iter()
+        .map(|&m| facelet_sources(m).map(|s| s as u8))
+        .collect();
+
+    let mut out = vec![(Vec::new(), std::array::from_fn(|i| i as u8))];
+    let mut start = 0;
+
+    for _ in 0..max_length {
+        let end = out.len();
+
+        for i in start..end {
+            for (move_ind, &m) in ALL_MOVES.iter().enumerate() {
+                let (moves, sources) = &out[i];
+                if moves.last().map(|last: &Move| last.dir) == Some(m.dir) {
+                    continue;
+                }
+
+                let mut next = moves.clone();
+                next.push(m);
+                let sources = then(sources, &move_sources[move_ind]);
+                out.push((next, sources));
+            }
+        }
+
+        start = end;
+    }
+
+    out
+}
+
+/// The shortest commutators and conjugates with the effect, counting the moves once cancelled
+/// (plain commutators first among those the same length), up to `count` of them, and no two with
+/// the same moves; each one is checked on a cube
+pub fn find_commutators(effect: &Effect, limits: Limits, count: usize) -> Vec<Commutator> {
+    let target: Sources = effect.sources().map(|s| s as u8);
+
+    // the commutator a setup needs is the effect seen from the other side of the setup
+    let mut wanted: HashMap<Sources, Vec<Vec<Move>>> = HashMap::new();
+    for (setup, sources) in sequences(limits.setup) {
+        let inner = then(&then(&inverse(&sources), &target), &sources);
+        wanted.entry(inner).or_default().push(setup);
+    }
+
+    let firsts = sequences(limits.first);
+    let seconds: Vec<(Vec<Move>, Sources, Sources)> = sequences(limits.second)
+        .into_iter()
+        .skip(1)
+        .map(|(moves, sources)| {
+            let undo = inverse(&sources);
+            (moves, sources, undo)
+        })
+        .collect();
+
+    let mut found = Vec::new();
+
+    for (first, first_sources) in firsts.iter().skip(1) {
+        let first_undo = inverse(first_sources);
+
+        for (second, second_sources, second_undo) in &seconds {
+            let effect = then(
+                &then(&then(first_sources, second_sources), &first_undo),
+                second_undo,
+            );
+
+            if let Some(setups) = wanted.get(&effect) {
+                for setup in setups {
+                    found.push(Commutator {
+                        setup: setup.clone(),
+                        first: first.clone(),
+                        second: second.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    found.sort_by_cached_key(|c| (c.len(), c.setup.len()));
+
+    // different ways of writing the same moves only count once
+    let mut seen: Vec<Vec<FullMove>> = Vec::new();
+    let mut out = Vec::new();
+    for commutator in found {
+        if out.len() == count {
+            break;
+        }
+
+        let moves = commutator.cancelled();
+        if seen.contains(&moves) || !verify(effect, &commutator) {
+            continue;
+        }
+
+        seen.push(moves);
+        out.push(commutator);
+    }
+
+    out
+}
+
+/// Whether doing the commutator to a solved cube has exactly the effect
+pub fn verify(effect: &Effect, commutator: &Commutator) -> bool {
+    let solved = ColorScheme::western().solved_cube();
+
+    let facelets = solved.to_facelets();
+    let wanted = effect.sources().map(|s| facelets[s].clone());
+
+    solved.apply_many(&commutator.moves()).to_facelets() == wanted
+}
+
+#[cfg(test)]
+mod comm_tests {
+    use CubeletPos::*;
+
+    use crate::moves::{Amt, Dir};
+
+    use super::*;
+
+    fn m(dir: Dir, amt: Amt) -> Move {
+        Move { dir, amt }
+    }
+
+    const LIMITS: Limits = Limits {
+        setup: 1,
+        first: 4,
+        second: 1,
+    };
+
+    #[test]
+    fn corner_three_cycles() {
+        let effect = Effect::Cycle([URF, ULF, ULB]);
+        let found = find_commutators(&effect, LIMITS, 5);
+
+        // without D moves, there's no eight move commutator for three corners of one layer; the
+        // best are twelve move conjugates, with two turns of R running together in the middle
+        assert!(!found.is_empty());
+        assert_eq!(found[0].len(), 11);
+        assert_eq!(found[0].moves().len(), 12);
+        assert!(found.iter().all(|c| verify(&effect, c)));
+
+        // going round the other way is a different effect, so a commutator for one isn't for both
+        let back = Effect::Cycle([URF, ULB, ULF]);
+        assert!(!verify(&back, &found[0]));
+    }
+
+    #[test]
+    fn twisting_two_corners() {
+        let effect = Effect::Twist([URF, URB]);
+
+        // a clockwise twist takes the U facelet of URF round to R
+        assert_eq!(
+            effect.sources()[CORNER_FACELETS[6][1]],
+            CORNER_FACELETS[6][0]
+        );
+
+        let limits = Limits {
+            setup: 1,
+            first: 6,
+            second: 1,
+        };
+        let found = find_commutators(&effect, limits, 3);
+        assert!(!found.is_empty());
+        assert!(found.iter().all(|c| verify(&effect, c)));
+    }
+
+    #[test]
+    fn no_rewritten_duplicates() {
+        let effect = Effect::Cycle([URF, ULF, ULB]);
+        let limits = Limits {
+            setup: 2,
+            first: 4,
+            second: 1,
+        };
+        let found = find_commutators(&effect, limits, 40);
+        assert!(!found.is_empty());
+
+        for (i, c) in found.iter().enumerate() {
+            assert!(found[i + 1..]
+                .iter()
+                .all(|d| d.cancelled() != c.cancelled()));
+        }
+        assert!(found.windows(2).all(|w| w[0].len() <= w[1].len()));
+    }
+
+    #[test]
+    fn setups_running_into_the_commutator() {
+        // the setup's R2 runs into the R starting the first part, and that's what makes it short
+        let effect = Effect::Cycle([URF, DRF, ULB]);
+        let limits = Limits {
+            setup: 2,
+            first: 5,
+            second: 2,
+        };
+        let found = find_commutators(&effect, limits, 1);
+
+        assert_eq!(found[0].len(), 11);
+        assert!(verify(&effect, &found[0]));
+
+        let r2 = Commutator {
+            setup: vec![m(Dir::R, Amt::Two)],
+            first: vec![
+                m(Dir::R, Amt::One),
+                m(Dir::F, Amt::One),
+                m(Dir::U, Amt::Rev),
+                m(Dir::F, Amt::One),
+            ],
+            second: vec![m(Dir::U, Amt::One)],
+        };
+        assert!(verify(&effect, &r2));
+        assert_eq!(nice_write(&r2.cancelled()), "R' F U' F U F' U F' R' U' R2");
+    }
+
+    #[test]
+    fn bad_effects() {
+        assert!(Effect::Cycle([URF, DLB, ULB]).check().is_err());
+        assert!(Effect::Twist([URF, URF]).check().is_err());
+        assert!(Effect::Twist([URF, DRF]).check().is_ok());
+    }
+}
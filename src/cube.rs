@@ -1,3 +1,5 @@
+use crate::color_scheme::ColorScheme;
+
 // A list of all cubies, white first, then yellow (don't change the order please)
 // Each cubie is in "order" clockwise, but without a set start cubie; that is, YBO is BOY is OYB,
 // but OBY is not equivalent and is not a cubie because that color combination does not occur on
@@ -27,15 +29,24 @@ pub enum Facelet {
     Green,
 }
 
+pub const ALL_FACELETS: [Facelet; 6] = [
+    Facelet::Yellow,
+    Facelet::Red,
+    Facelet::White,
+    Facelet::Orange,
+    Facelet::Blue,
+    Facelet::Green,
+];
+
 impl Facelet {
-    pub fn opposite(&self) -> Self {
+    pub fn letter(&self) -> char {
         match self {
-            Facelet::Yellow => Facelet::White,
-            Facelet::White => Facelet::Yellow,
-            Facelet::Red => Facelet::Orange,
-            Facelet::Orange => Facelet::Red,
-            Facelet::Blue => Facelet::Green,
-            Facelet::Green => Facelet::Blue,
+            Facelet::Yellow => 'Y',
+            Facelet::Red => 'R',
+            Facelet::White => 'W',
+            Facelet::Orange => 'O',
+            Facelet::Blue => 'B',
+            Facelet::Green => 'G',
         }
     }
 }
@@ -138,6 +149,35 @@ impl CubeletOrientationArrangement {
         self == &CubeletOrientationArrangement::make_solved()
     }
 
+    /// The arrangement as an array, indexed in the order of [CubeletPos::index]
+    pub fn to_ints(&self) -> [i32; 8] {
+        [
+            self.dlb.to_int(),
+            self.dlf.to_int(),
+            self.drf.to_int(),
+            self.drb.to_int(),
+            self.ulb.to_int(),
+            self.ulf.to_int(),
+            self.urf.to_int(),
+            self.urb.to_int(),
+        ]
+    }
+
+    pub fn from_ints(ints: [i32; 8]) -> Self {
+        let [dlb, dlf, drf, drb, ulb, ulf, urf, urb] = ints.map(CubeletOrientation::from_int);
+
+        Self {
+            ulf,
+            ulb,
+            urf,
+            urb,
+            dlf,
+            dlb,
+            drf,
+            drb,
+        }
+    }
+
     pub fn is_solvable(&self) -> bool {
         let total: i32 = [
             self.ulf.to_int(),
@@ -152,7 +192,7 @@ impl CubeletOrientationArrangement {
         .into_iter()
         .sum();
 
-        return (total % 3) == 0;
+        (total % 3) == 0
     }
 
     #[inline(always)]
@@ -292,6 +332,35 @@ impl CubeletPositionArrangement {
         }
     }
 
+    /// The arrangement as an array, indexed in the order of [CubeletPos::index]
+    pub fn to_indices(&self) -> [usize; 8] {
+        [
+            self.dlb.index(),
+            self.dlf.index(),
+            self.drf.index(),
+            self.drb.index(),
+            self.ulb.index(),
+            self.ulf.index(),
+            self.urf.index(),
+            self.urb.index(),
+        ]
+    }
+
+    pub fn from_indices(indices: [usize; 8]) -> Self {
+        let [dlb, dlf, drf, drb, ulb, ulf, urf, urb] = indices.map(CubeletPos::from_index);
+
+        Self {
+            ulf,
+            ulb,
+            urf,
+            urb,
+            dlf,
+            dlb,
+            drf,
+            drb,
+        }
+    }
+
     #[inline(always)]
     pub fn is_solved(&self) -> bool {
         // should optimize to 8 equality checks pretty readily
@@ -425,6 +494,18 @@ impl DesiredFaces {
     fn is_side_facelet(&self, a: Facelet) -> bool {
         self.l == a || self.r == a
     }
+
+    /// The solved cube these faces describe
+    pub fn solved_cube(&self) -> Cube {
+        Cube::from_faces(
+            self.u.clone(),
+            self.d.clone(),
+            self.r.clone(),
+            self.l.clone(),
+            self.f.clone(),
+            self.b.clone(),
+        )
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
@@ -456,6 +537,14 @@ impl CubeletOrientation {
             CubeletOrientation::CCW => 2,
         }
     }
+
+    pub fn from_int(i: i32) -> Self {
+        match i.rem_euclid(3) {
+            0 => CubeletOrientation::OK,
+            1 => CubeletOrientation::CW,
+            _ => CubeletOrientation::CCW,
+        }
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
@@ -471,6 +560,39 @@ pub enum CubeletPos {
 }
 
 impl CubeletPos {
+    /// The index of this position, in the order DLB, DLF, DRF, DRB, ULB, ULF, URF, URB (which is
+    /// the same order `put_cubie` uses)
+    pub fn index(&self) -> usize {
+        use CubeletPos::*;
+
+        match self {
+            DLB => 0,
+            DLF => 1,
+            DRF => 2,
+            DRB => 3,
+            ULB => 4,
+            ULF => 5,
+            URF => 6,
+            URB => 7,
+        }
+    }
+
+    pub fn from_index(i: usize) -> Self {
+        use CubeletPos::*;
+
+        match i {
+            0 => DLB,
+            1 => DLF,
+            2 => DRF,
+            3 => DRB,
+            4 => ULB,
+            5 => ULF,
+            6 => URF,
+            7 => URB,
+            other => panic!("Bad position index: {}", other),
+        }
+    }
+
     fn from_pos(u: bool, r: bool, f: bool) -> CubeletPos {
         use CubeletPos::*;
 
@@ -487,31 +609,6 @@ impl CubeletPos {
     }
 }
 
-/// Returns the "next" color for a (corner) cubie, in clockwise order, starting from a, to b,
-/// to (the return value).
-fn next_color(a: Facelet, b: Facelet) -> Facelet {
-    if a.opposite() == b || a == b {
-        panic!(
-            "The colors {:?} and {:?} do not appear on a corner together",
-            a, b
-        )
-    }
-
-    for cubie in ALL_CUBIES {
-        for i in 0..3 {
-            if a == cubie[i] {
-                let next = (i + 1) % 3;
-                if b == cubie[next] {
-                    let last = (next + 1) % 3;
-                    return cubie[last].clone();
-                }
-            }
-        }
-    }
-
-    unreachable!("Given the facelets {:?} and {:?} which are not equal or opposites, we should have found a third facelet, but didn't", a, b)
-}
-
 /// Determines the orientation of a cubelet based on the desired face colors, the facelet currently
 /// on the side, and the facelet which would be on the side if the cubelet were rotated clockwise
 fn make_orr_from_desired_faces(
@@ -568,62 +665,212 @@ fn make_pos_from_desired_faces(
     )
 }
 
+/// The indices (into [Cube::to_facelets]) of the facelets of each corner, in the same order as
+/// [Cube::corners]
+pub const CORNER_FACELETS: [[usize; 3]; 8] = [
+    [14, 23, 18],
+    [12, 19, 10],
+    [13, 11, 6],
+    [15, 7, 22],
+    [0, 16, 21],
+    [2, 8, 17],
+    [3, 4, 9],
+    [1, 20, 5],
+];
+
+/// Where each position's L or R facelet is in its [Cube::corners] triple; orientations are counted
+/// from that facelet
+const SIDE_INDEX: [usize; 8] = [2, 1, 2, 1, 1, 2, 1, 2];
+
+/// When the cubie which belongs at position `cubie` sits at position `pos` with the given
+/// orientation (both by [CubeletPos::index]), its facelets are its solved ones turned this many
+/// steps; that is, `corners()[pos][k]` is the solved `corners()[cubie][(k + turn) % 3]`. This is
+/// also the orientation `put_cubie` wants to put it back that way.
+pub fn corner_turn(pos: usize, cubie: usize, orientation: &CubeletOrientation) -> usize {
+    (SIDE_INDEX[cubie] + 6 - SIDE_INDEX[pos] - orientation.to_int() as usize) % 3
+}
+
 impl Cube {
+    /// The solved cube (in the western color scheme) with the given colors on the front and top
     pub fn make_solved(front_color: Facelet, up_color: Facelet) -> Self {
-        let back_color = front_color.opposite();
-        let down_color = up_color.opposite();
+        Self::make_solved_with(&ColorScheme::default(), front_color, up_color)
+    }
 
-        let right_color = next_color(front_color.clone(), up_color.clone());
-        let left_color = right_color.opposite();
+    /// The solved cube in the given color scheme, with the given colors on the front and top
+    pub fn make_solved_with(scheme: &ColorScheme, front_color: Facelet, up_color: Facelet) -> Self {
+        let back_color = scheme.opposite(&front_color);
+        let down_color = scheme.opposite(&up_color);
+
+        let right_color = scheme.next_color(&front_color, &up_color);
+        let left_color = scheme.opposite(&right_color);
+
+        Self::from_faces(
+            up_color,
+            down_color,
+            right_color,
+            left_color,
+            front_color,
+            back_color,
+        )
+    }
 
+    /// The solved cube with the given face colors; this doesn't check that they make sense
+    pub fn from_faces(
+        u: Facelet,
+        d: Facelet,
+        r: Facelet,
+        l: Facelet,
+        f: Facelet,
+        b: Facelet,
+    ) -> Self {
         Self {
             u: UDFace {
-                bl: up_color.clone(),
-                br: up_color.clone(),
-                fl: up_color.clone(),
-                fr: up_color,
+                bl: u.clone(),
+                br: u.clone(),
+                fl: u.clone(),
+                fr: u,
             },
             d: UDFace {
-                bl: down_color.clone(),
-                br: down_color.clone(),
-                fl: down_color.clone(),
-                fr: down_color,
+                bl: d.clone(),
+                br: d.clone(),
+                fl: d.clone(),
+                fr: d,
             },
             r: LRFace {
-                ub: right_color.clone(),
-                uf: right_color.clone(),
-                db: right_color.clone(),
-                df: right_color,
+                ub: r.clone(),
+                uf: r.clone(),
+                db: r.clone(),
+                df: r,
             },
             l: LRFace {
-                ub: left_color.clone(),
-                uf: left_color.clone(),
-                db: left_color.clone(),
-                df: left_color,
+                ub: l.clone(),
+                uf: l.clone(),
+                db: l.clone(),
+                df: l,
             },
             f: FBFace {
-                ul: front_color.clone(),
-                ur: front_color.clone(),
-                dl: front_color.clone(),
-                dr: front_color,
+                ul: f.clone(),
+                ur: f.clone(),
+                dl: f.clone(),
+                dr: f,
             },
             b: FBFace {
-                ul: back_color.clone(),
-                ur: back_color.clone(),
-                dl: back_color.clone(),
-                dr: back_color,
+                ul: b.clone(),
+                ur: b.clone(),
+                dl: b.clone(),
+                dr: b,
             },
         }
     }
 
+    /// All 24 facelets, face by face in the order U, R, F, D, L, B. Each face is read left to
+    /// right, top to bottom, as it looks in the unfolded net (U above F with B at the top, L R and B
+    /// to the sides of F, and D below F with F at the top).
+    pub fn to_facelets(&self) -> [Facelet; 24] {
+        let Self { u, d, r, l, f, b } = self.clone();
+
+        [
+            u.bl, u.br, u.fl, u.fr, //
+            r.uf, r.ub, r.df, r.db, //
+            f.ul, f.ur, f.dl, f.dr, //
+            d.fl, d.fr, d.bl, d.br, //
+            l.ub, l.uf, l.db, l.df, //
+            b.ur, b.ul, b.dr, b.dl, //
+        ]
+    }
+
+    /// The inverse of [Cube::to_facelets]; this doesn't check whether the result is a real cube
+    pub fn from_facelets(facelets: [Facelet; 24]) -> Self {
+        let [u_bl, u_br, u_fl, u_fr, r_uf, r_ub, r_df, r_db, f_ul, f_ur, f_dl, f_dr, d_fl, d_fr, d_bl, d_br, l_ub, l_uf, l_db, l_df, b_ur, b_ul, b_dr, b_dl] =
+            facelets;
+
+        Self {
+            u: UDFace {
+                bl: u_bl,
+                br: u_br,
+                fl: u_fl,
+                fr: u_fr,
+            },
+            d: UDFace {
+                bl: d_bl,
+                br: d_br,
+                fl: d_fl,
+                fr: d_fr,
+            },
+            r: LRFace {
+                ub: r_ub,
+                uf: r_uf,
+                db: r_db,
+                df: r_df,
+            },
+            l: LRFace {
+                ub: l_ub,
+                uf: l_uf,
+                db: l_db,
+                df: l_df,
+            },
+            f: FBFace {
+                ul: f_ul,
+                ur: f_ur,
+                dl: f_dl,
+                dr: f_dr,
+            },
+            b: FBFace {
+                ul: b_ul,
+                ur: b_ur,
+                dl: b_dl,
+                dr: b_dr,
+            },
+        }
+    }
+
+    /// The facelets as letters, in the order of [Cube::to_facelets]; [crate::setup::parse_facelets]
+    /// reads this back in
+    pub fn to_facelet_string(&self) -> String {
+        self.to_facelets().iter().map(Facelet::letter).collect()
+    }
+
+    /// The facelets of each corner, in clockwise order starting from the U or D facelet, indexed
+    /// by [CubeletPos::index]; this is the same order `put_cubie` uses
+    pub fn corners(&self) -> [[Facelet; 3]; 8] {
+        let Self { u, d, r, l, f, b } = self.clone();
+
+        [
+            [d.bl, b.dl, l.db],
+            [d.fl, l.df, f.dl],
+            [d.fr, f.dr, r.df],
+            [d.br, r.db, b.dr],
+            [u.bl, l.ub, b.ul],
+            [u.fl, f.ul, l.uf],
+            [u.fr, r.uf, f.ur],
+            [u.br, b.ur, r.ub],
+        ]
+    }
+
+    /// The color opposite to the given one on this cube; that is, the only other color which
+    /// never shares a corner with it. This works in any color scheme.
+    pub fn opposite_color(&self, color: &Facelet) -> Facelet {
+        let corners = self.corners();
+
+        ALL_FACELETS
+            .into_iter()
+            .find(|other| {
+                other != color
+                    && !corners
+                        .iter()
+                        .any(|corner| corner.contains(color) && corner.contains(other))
+            })
+            .unwrap_or_else(|| panic!("Could not find the opposite of {:?} on the cube", color))
+    }
+
     pub fn make_desired_from_dlb(&self) -> DesiredFaces {
         let l = self.l.db.clone();
         let d = self.d.bl.clone();
         let b = self.b.dl.clone();
 
-        let r = l.opposite();
-        let u = d.opposite();
-        let f = b.opposite();
+        let r = self.opposite_color(&l);
+        let u = self.opposite_color(&d);
+        let f = self.opposite_color(&b);
 
         DesiredFaces { l, d, b, r, u, f }
     }
@@ -1029,30 +1276,37 @@ mod invariants_tests {
 
     #[test]
     fn opp_test() {
-        assert_eq!(Facelet::Yellow.opposite(), Facelet::White);
-        assert_eq!(Facelet::White.opposite(), Facelet::Yellow);
-        assert_eq!(Facelet::Green.opposite(), Facelet::Blue);
-        assert_eq!(Facelet::Blue.opposite(), Facelet::Green);
-        assert_eq!(Facelet::Red.opposite(), Facelet::Orange);
-        assert_eq!(Facelet::Orange.opposite(), Facelet::Red);
+        let scheme = ColorScheme::western();
+
+        assert_eq!(scheme.opposite(&Facelet::Yellow), Facelet::White);
+        assert_eq!(scheme.opposite(&Facelet::White), Facelet::Yellow);
+        assert_eq!(scheme.opposite(&Facelet::Green), Facelet::Blue);
+        assert_eq!(scheme.opposite(&Facelet::Blue), Facelet::Green);
+        assert_eq!(scheme.opposite(&Facelet::Red), Facelet::Orange);
+        assert_eq!(scheme.opposite(&Facelet::Orange), Facelet::Red);
+
+        let cube = scheme.solved_cube().right().front_rev().up_two();
 
         for v in facelets() {
-            assert_eq!(v.opposite().opposite(), v);
+            assert_eq!(scheme.opposite(&scheme.opposite(&v)), v);
+            assert_eq!(cube.opposite_color(&v), scheme.opposite(&v));
         }
     }
 
     #[test]
     fn corner_exists_test() {
+        let scheme = ColorScheme::western();
+
         for a in facelets() {
             for b in facelets() {
-                if a != b && a.opposite() != b {
+                if a != b && scheme.opposite(&a) != b {
                     // basically just assert we get _something_
-                    let c = next_color(a.clone(), b.clone());
+                    let c = scheme.next_color(&a, &b);
                     assert_ne!(a, c);
                     assert_ne!(b, c);
                 } else {
                     // otherwise, we do want to make sure it panics, there should be nothing there
-                    should_panic(|| next_color(a.clone(), b.clone()));
+                    should_panic(|| scheme.next_color(&a, &b));
                 }
             }
         }
@@ -1060,9 +1314,11 @@ mod invariants_tests {
 
     #[test]
     fn solved_cube_exists() {
+        let scheme = ColorScheme::western();
+
         for a in facelets() {
             for b in facelets() {
-                if a != b && a.opposite() != b {
+                if a != b && scheme.opposite(&a) != b {
                     // basically just assert we get _something_
                     let _ = Cube::make_solved(a.clone(), b.clone());
                 } else {
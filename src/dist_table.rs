@@ -0,0 +1,395 @@
+//! A complete table of optimal solution lengths for every state of the pocket cube.
+//!
+//! With the DLB cubie fixed, a state is exactly a (position, orientation) arrangement pair, and
+//! there are only 7! * 3^6 = 3,674,160 of those. That's small enough to just walk the whole thing
+//! breadth-first from solved and write down how far away everything is, which makes the table an
+//! exact distance oracle (and a perfect IDA* heuristic, for what it's worth).
+
+use std::collections::HashMap;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::color_scheme::ColorScheme;
+use crate::cube::{Cube, CubeletOrientationArrangement, CubeletPositionArrangement};
+use crate::moves::{flipped, Amt, CanMove, Move, MoveSet, ALL_MOVES};
+
+/// Number of position arrangements with DLB fixed (7!)
+pub const NUM_POSITIONS: usize = 5040;
+/// Number of solvable orientation arrangements with DLB fixed (3^6)
+pub const NUM_ORIENTATIONS: usize = 729;
+/// Number of states in the whole (DLB fixed) state space
+pub const NUM_STATES: usize = NUM_POSITIONS * NUM_ORIENTATIONS;
+
+const UNKNOWN: u8 = u8::MAX;
+
+/// How to count the length of a move sequence
+#[derive(ValueEnum, Serialize, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Metric {
+    /// Half turn metric: every turn of a face counts as one move, including half turns
+    HTM,
+    /// Quarter turn metric: a half turn counts as two moves
+    QTM,
+}
+
+impl Metric {
+    /// The indices (into [ALL_MOVES]) of the moves which count as a single move
+    pub fn move_indices(self) -> Vec<usize> {
+        (0..ALL_MOVES.len())
+            .filter(|&i| self == Metric::HTM || ALL_MOVES[i].amt != Amt::Two)
+            .collect()
+    }
+}
+
+/// Walks a state space breadth-first from the `starts`, writing down how many moves away every
+/// state is from the nearest of them (or [u8::MAX] if the moves never get there);
+/// `step(state, move_ind)` is where the move takes the state
+fn walk(
+    size: usize,
+    starts: &[usize],
+    moves: &[usize],
+    step: impl Fn(usize, usize) -> usize,
+) -> Vec<u8> {
+    let mut distances = vec![UNKNOWN; size];
+    for &start in starts {
+        distances[start] = 0;
+    }
+
+    // one layer at a time; every state of distance d+1 is one move from something at distance d
+    let mut frontier = starts.to_vec();
+    let mut depth = 0;
+
+    while !frontier.is_empty() {
+        let mut next_frontier = Vec::new();
+
+        for &state in &frontier {
+            for &move_ind in moves {
+                let next = step(state, move_ind);
+                if distances[next] == UNKNOWN {
+                    distances[next] = depth + 1;
+                    next_frontier.push(next);
+                }
+            }
+        }
+
+        frontier = next_frontier;
+        depth += 1;
+    }
+
+    distances
+}
+
+/// How many states there are at each distance, from 0 up to the furthest (leaving out any which
+/// were never reached)
+pub fn histogram(distances: &[u8]) -> Vec<usize> {
+    let reached = || distances.iter().copied().filter(|&d| d != UNKNOWN);
+    let max = reached().max().unwrap_or(0) as usize;
+
+    let mut out = vec![0; max + 1];
+    for d in reached() {
+        out[d as usize] += 1;
+    }
+
+    out
+}
+
+pub fn rank_pos(pos: &CubeletPositionArrangement) -> usize {
+    let indices = pos.to_indices();
+
+    assert_eq!(indices[0], 0, "DLB must be fixed to rank a position");
+
+    // Lehmer code of the other seven cubelets
+    let mut rank = 0;
+    for i in 1..8 {
+        let smaller_later = indices[i + 1..].iter().filter(|&&j| j < indices[i]).count();
+        rank = rank * (8 - i) + smaller_later;
+    }
+
+    rank
+}
+
+pub fn unrank_pos(mut rank: usize) -> CubeletPositionArrangement {
+    let mut digits = [0; 8];
+    for i in (1..8).rev() {
+        digits[i] = rank % (8 - i);
+        rank /= 8 - i;
+    }
+
+    let mut available: Vec<usize> = (1..8).collect();
+    let mut indices = [0; 8];
+    for i in 1..8 {
+        indices[i] = available.remove(digits[i]);
+    }
+
+    CubeletPositionArrangement::from_indices(indices)
+}
+
+pub fn rank_orr(orr: &CubeletOrientationArrangement) -> usize {
+    let ints = orr.to_ints();
+
+    assert_eq!(ints[0], 0, "DLB must be fixed to rank an orientation");
+
+    // the last cubelet is determined by the others, since the total orientation is zero
+    ints[1..7].iter().fold(0, |acc, &i| acc * 3 + i as usize)
+}
+
+pub fn unrank_orr(mut rank: usize) -> CubeletOrientationArrangement {
+    let mut ints = [0; 8];
+    for i in (1..7).rev() {
+        ints[i] = (rank % 3) as i32;
+        rank /= 3;
+    }
+
+    let total: i32 = ints.iter().sum();
+    ints[7] = (3 - total % 3) % 3;
+
+    CubeletOrientationArrangement::from_ints(ints)
+}
+
+pub fn rank_state(pos: &CubeletPositionArrangement, orr: &CubeletOrientationArrangement) -> usize {
+    rank_pos(pos) * NUM_ORIENTATIONS + rank_orr(orr)
+}
+
+pub fn rank_cube(cube: &Cube) -> usize {
+    rank_state(
+        &cube.clone().make_pos_arr_from_dlb(),
+        &cube.clone().make_orr_arr_from_dlb(),
+    )
+}
+
+pub struct DistanceTable {
+    distances: Vec<u8>,
+    /// The moves counted as one, see [Metric::move_indices]
+    moves: Vec<usize>,
+    // since moves act on positions and orientations independently, we can precompute what every
+    // move does to each of them separately, and then the walk is just table lookups
+    pos_moves: Vec<[u16; 9]>,
+    orr_moves: Vec<[u16; 9]>,
+}
+
+impl DistanceTable {
+    pub fn build() -> Self {
+        Self::build_in(Metric::HTM)
+    }
+
+    /// The table of distances counted in the given metric
+    pub fn build_in(metric: Metric) -> Self {
+        let solved = rank_state(
+            &CubeletPositionArrangement::make_solved(),
+            &CubeletOrientationArrangement::make_solved(),
+        );
+
+        Self::build_towards(metric.move_indices(), &[solved])
+    }
+
+    /// The table of distances to the nearest of the `goals` (by [rank_state]), using only the
+    /// moves in the set; states the set can't reach the goals from are [u8::MAX] away
+    pub fn build_restricted(moves: &MoveSet, goals: &[usize]) -> Self {
+        Self::build_towards(moves.indices().to_vec(), goals)
+    }
+
+    /// `moves` have to include the inverse of each move, so that walking out from the goals is
+    /// the same as walking towards them
+    fn build_towards(moves: Vec<usize>, goals: &[usize]) -> Self {
+        let pos_moves: Vec<[u16; 9]> = (0..NUM_POSITIONS)
+            .map(|rank| {
+                let pos = unrank_pos(rank);
+                ALL_MOVES.map(|m| rank_pos(&pos.clone().apply(m)) as u16)
+            })
+            .collect();
+
+        let orr_moves: Vec<[u16; 9]> = (0..NUM_ORIENTATIONS)
+            .map(|rank| {
+                let orr = unrank_orr(rank);
+                ALL_MOVES.map(|m| rank_orr(&orr.clone().apply(m)) as u16)
+            })
+            .collect();
+
+        let mut table = DistanceTable {
+            distances: Vec::new(),
+            moves,
+            pos_moves,
+            orr_moves,
+        };
+
+        table.distances = walk(NUM_STATES, goals, &table.moves, |state, move_ind| {
+            table.apply_move(state, move_ind)
+        });
+
+        table
+    }
+
+    #[inline(always)]
+    fn apply_move(&self, state: usize, move_ind: usize) -> usize {
+        let pos = self.pos_moves[state / NUM_ORIENTATIONS][move_ind] as usize;
+        let orr = self.orr_moves[state % NUM_ORIENTATIONS][move_ind] as usize;
+
+        pos * NUM_ORIENTATIONS + orr
+    }
+
+    /// The optimal solution length of the state with the given rank
+    pub fn state_distance(&self, state: usize) -> usize {
+        self.distances[state] as usize
+    }
+
+    /// The longest optimal solution length of any state
+    pub fn max_distance(&self) -> usize {
+        self.histogram().len() - 1
+    }
+
+    /// How many states there are at each distance
+    pub fn histogram(&self) -> Vec<usize> {
+        histogram(&self.distances)
+    }
+
+    /// The distance of every position arrangement (by [rank_pos]) from solved, ignoring
+    /// orientation, in the same metric as this table
+    pub fn position_distances(&self) -> Vec<u8> {
+        let solved = rank_pos(&CubeletPositionArrangement::make_solved());
+        walk(NUM_POSITIONS, &[solved], &self.moves, |pos, move_ind| {
+            self.pos_moves[pos][move_ind] as usize
+        })
+    }
+
+    /// The distance of every orientation arrangement (by [rank_orr]) from solved, ignoring
+    /// position, in the same metric as this table
+    pub fn orientation_distances(&self) -> Vec<u8> {
+        let solved = rank_orr(&CubeletOrientationArrangement::make_solved());
+        walk(NUM_ORIENTATIONS, &[solved], &self.moves, |orr, move_ind| {
+            self.orr_moves[orr][move_ind] as usize
+        })
+    }
+
+    /// The ranks of every state whose optimal solution length is exactly `distance`
+    pub fn states_at_distance(&self, distance: usize) -> Vec<usize> {
+        (0..NUM_STATES)
+            .filter(|&state| self.state_distance(state) == distance)
+            .collect()
+    }
+
+    /// An optimal solution for the state with the given rank; just walk downhill
+    pub fn solve_state(&self, mut state: usize) -> Vec<Move> {
+        let mut out = Vec::with_capacity(self.state_distance(state));
+
+        while self.distances[state] > 0 {
+            let goal = self.distances[state] - 1;
+
+            let (move_ind, next) = self
+                .moves
+                .iter()
+                .map(|&move_ind| (move_ind, self.apply_move(state, move_ind)))
+                .find(|&(_, next)| self.distances[next] == goal)
+                .expect("Every unsolved state should have a neighbor which is closer to solved");
+
+            out.push(ALL_MOVES[move_ind]);
+            state = next;
+        }
+
+        out
+    }
+
+    /// How many different move sequences (with DLB fixed, and in this table's metric) solve the state with the given rank in
+    /// the optimal number of moves
+    pub fn count_optimal_solutions(&self, state: usize) -> u64 {
+        fn count(table: &DistanceTable, state: usize, memo: &mut HashMap<usize, u64>) -> u64 {
+            if table.distances[state] == 0 {
+                return 1;
+            } else if let Some(&known) = memo.get(&state) {
+                return known;
+            }
+
+            let goal = table.distances[state] - 1;
+            let total = table
+                .moves
+                .iter()
+                .map(|&move_ind| table.apply_move(state, move_ind))
+                .filter(|&next| table.distances[next] == goal)
+                .map(|next| count(table, next, memo))
+                .sum();
+
+            memo.insert(state, total);
+            total
+        }
+
+        count(self, state, &mut HashMap::new())
+    }
+
+    /// The optimal solution length of the given cube
+    pub fn distance(&self, cube: &Cube) -> usize {
+        self.state_distance(rank_cube(cube))
+    }
+
+    /// An optimal solution for the given cube
+    pub fn solve(&self, cube: &Cube) -> Vec<Move> {
+        self.solve_state(rank_cube(cube))
+    }
+
+    /// Builds an actual cube (in the colors of the scheme) in the state with the given rank, by
+    /// running one of its optimal solutions backward from solved
+    pub fn cube_for_state(&self, state: usize, scheme: &ColorScheme) -> Cube {
+        let scramble = flipped(&self.solve_state(state));
+
+        scheme.solved_cube().apply_many(&scramble)
+    }
+}
+
+#[cfg(test)]
+mod dist_table_tests {
+    use crate::moves::CanFullMove;
+    use crate::setup::parse_line;
+
+    use super::*;
+
+    #[test]
+    fn rank_round_trip() {
+        for rank in 0..NUM_POSITIONS {
+            assert_eq!(rank_pos(&unrank_pos(rank)), rank);
+        }
+
+        for rank in 0..NUM_ORIENTATIONS {
+            let orr = unrank_orr(rank);
+            assert!(orr.is_solvable());
+            assert_eq!(rank_orr(&orr), rank);
+        }
+    }
+
+    #[test]
+    fn full_table() {
+        let table = DistanceTable::build();
+
+        // the well-known half turn metric distribution for the pocket cube
+        let expected: [usize; 12] = [
+            1, 9, 54, 321, 1847, 9992, 50136, 227536, 870072, 1887748, 623800, 2644,
+        ];
+
+        assert_eq!(table.max_distance(), 11);
+
+        for (dist, &count) in expected.iter().enumerate() {
+            assert_eq!(table.states_at_distance(dist).len(), count);
+        }
+
+        // same problem child as the full_solve tests
+        let moves = parse_line("F2 R' F' F2 U2 R2 F R U' R U2 R' L").unwrap();
+        let cube = ColorScheme::western().solved_cube().apply_many_full(&moves);
+
+        let solution = table.solve(&cube);
+        assert_eq!(solution.len(), 9);
+        assert_eq!(table.distance(&cube), 9);
+        assert!(table.count_optimal_solutions(rank_cube(&cube)) >= 1);
+        assert!(cube.apply_many(&solution).solved());
+
+        // two moves on different faces never cancel, so each of those 9 * 6 sequences is the only
+        // optimal solution to its own state
+        let two_away: Vec<u64> = table
+            .states_at_distance(2)
+            .into_iter()
+            .map(|state| table.count_optimal_solutions(state))
+            .collect();
+        assert!(two_away.iter().all(|&count| count == 1));
+
+        let state = table.states_at_distance(11)[0];
+        let cube = table.cube_for_state(state, &ColorScheme::western());
+        assert_eq!(rank_cube(&cube), state);
+        assert_eq!(table.state_distance(rank_cube(&cube)), 11);
+    }
+}
@@ -0,0 +1,247 @@
+//! How many states are at each distance from solved, for the whole cube and for the position and
+//! orientation subproblems the heuristics are built on; both raw, and counting each class of
+//! symmetric states (see [crate::symmetry]) once.
+
+use serde::Serialize;
+
+use crate::color_scheme::ColorScheme;
+use crate::cube::{CubeletOrientationArrangement, CubeletPositionArrangement, CORNER_FACELETS};
+use crate::dist_table::{
+    histogram, rank_cube, rank_orr, rank_pos, unrank_orr, unrank_pos, DistanceTable, Metric,
+    NUM_STATES,
+};
+use crate::symmetry::{all_symmetries, Symmetry};
+
+/// The facelet positions on the L and R faces, which orientations are counted from
+const SIDE_FACELETS: [usize; 8] = [4, 5, 6, 7, 16, 17, 18, 19];
+
+#[derive(Serialize, Debug)]
+pub struct Distribution {
+    pub metric: Metric,
+    /// Indexed by distance
+    pub states: Vec<usize>,
+    pub state_classes: Vec<usize>,
+    pub positions: Vec<usize>,
+    pub position_classes: Vec<usize>,
+    pub orientations: Vec<usize>,
+    pub orientation_classes: Vec<usize>,
+}
+
+const HEADINGS: [&str; 7] = [
+    "distance",
+    "states",
+    "states_up_to_symmetry",
+    "positions",
+    "positions_up_to_symmetry",
+    "orientations",
+    "orientations_up_to_symmetry",
+];
+
+/// Counts each class of states once, at its distance. `images(state)` is every state symmetric to
+/// the given one, including itself.
+fn count_classes(distances: &[u8], images: impl Fn(usize) -> Vec<usize>) -> Vec<usize> {
+    let mut seen = vec![false; distances.len()];
+    let mut out = vec![0; histogram(distances).len()];
+
+    for state in 0..distances.len() {
+        if seen[state] {
+            continue;
+        }
+
+        out[distances[state] as usize] += 1;
+        for image in images(state) {
+            seen[image] = true;
+        }
+    }
+
+    out
+}
+
+impl Distribution {
+    pub fn compute(metric: Metric) -> Self {
+        let table = DistanceTable::build_in(metric);
+        let symmetries = all_symmetries();
+
+        let solved_pos = CubeletPositionArrangement::make_solved();
+        let solved_orr = CubeletOrientationArrangement::make_solved();
+
+        let states: Vec<u8> = (0..NUM_STATES)
+            .map(|s| table.state_distance(s) as u8)
+            .collect();
+        let state_classes = count_classes(&states, |state| {
+            let cube = table.cube_for_state(state, &ColorScheme::western());
+            symmetries
+                .iter()
+                .map(|s| rank_cube(&s.apply(&cube)))
+                .collect()
+        });
+
+        // the subproblems only make sense up to the symmetries which keep DLB where it is, and
+        // for orientations, which keep the L and R facelets on the sides
+        let fixing_dlb: Vec<&Symmetry> = symmetries
+            .iter()
+            .filter(|s| s.preserves(&CORNER_FACELETS[0]))
+            .collect();
+        let keeping_sides: Vec<&Symmetry> = fixing_dlb
+            .iter()
+            .copied()
+            .filter(|s| s.preserves(&SIDE_FACELETS))
+            .collect();
+
+        let positions = table.position_distances();
+        let position_classes = count_classes(&positions, |pos| {
+            let pos = unrank_pos(pos);
+            fixing_dlb
+                .iter()
+                .map(|s| rank_pos(&s.apply_arrangements(&pos, &solved_orr).0))
+                .collect()
+        });
+
+        let orientations = table.orientation_distances();
+        let orientation_classes = count_classes(&orientations, |orr| {
+            let orr = unrank_orr(orr);
+            keeping_sides
+                .iter()
+                .map(|s| rank_orr(&s.apply_arrangements(&solved_pos, &orr).1))
+                .collect()
+        });
+
+        Distribution {
+            metric,
+            states: table.histogram(),
+            state_classes,
+            positions: histogram(&positions),
+            position_classes,
+            orientations: histogram(&orientations),
+            orientation_classes,
+        }
+    }
+
+    fn columns(&self) -> [&Vec<usize>; 6] {
+        [
+            &self.states,
+            &self.state_classes,
+            &self.positions,
+            &self.position_classes,
+            &self.orientations,
+            &self.orientation_classes,
+        ]
+    }
+
+    /// One row per distance (then a row of totals), with the count in each column; blank where
+    /// nothing is that far away
+    fn rows(&self) -> Vec<[String; 7]> {
+        let columns = self.columns();
+        let longest = self.states.len();
+
+        let mut rows: Vec<[String; 7]> = (0..longest)
+            .map(|d| {
+                std::array::from_fn(|c| match c {
+                    0 => d.to_string(),
+                    c => columns[c - 1]
+                        .get(d)
+                        .map_or(String::new(), |count| count.to_string()),
+                })
+            })
+            .collect();
+
+        rows.push(std::array::from_fn(|c| match c {
+            0 => "total".to_string(),
+            c => columns[c - 1].iter().sum::<usize>().to_string(),
+        }));
+
+        rows
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut out = HEADINGS.join(",");
+        out.push('\n');
+
+        for row in self.rows() {
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+
+        out
+    }
+
+    pub fn to_table(&self) -> String {
+        let headings = [
+            "Distance",
+            "States",
+            "(classes)",
+            "Positions",
+            "(classes)",
+            "Orientations",
+            "(classes)",
+        ];
+        let rows = self.rows();
+
+        let widths: [usize; 7] = std::array::from_fn(|c| {
+            rows.iter()
+                .map(|row| row[c].len())
+                .chain([headings[c].len()])
+                .max()
+                .unwrap()
+        });
+
+        let line = |cells: [&str; 7]| -> String {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+                .collect();
+            padded.join("  ")
+        };
+
+        let mut out = line(headings);
+        out.push('\n');
+        for row in &rows {
+            out.push_str(&line(std::array::from_fn(|c| row[c].as_str())));
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod distribution_tests {
+    use crate::dist_table::{NUM_ORIENTATIONS, NUM_POSITIONS};
+
+    use super::*;
+
+    #[test]
+    fn subproblems_and_classes() {
+        let dist = Distribution::compute(Metric::HTM);
+
+        assert_eq!(dist.states.iter().sum::<usize>(), NUM_STATES);
+        assert_eq!(dist.positions.iter().sum::<usize>(), NUM_POSITIONS);
+        assert_eq!(dist.orientations.iter().sum::<usize>(), NUM_ORIENTATIONS);
+
+        // solved is alone, and every single move is either a quarter turn or a half turn
+        assert_eq!(&dist.state_classes[..2], &[1, 2]);
+        assert_eq!(dist.state_classes.iter().sum::<usize>(), 77_802);
+
+        // R doesn't change orientation, and the other faces' moves only get to two orientations
+        assert_eq!(&dist.orientations[..2], &[1, 2]);
+
+        let csv = dist.to_csv();
+        assert!(csv.starts_with("distance,states,"));
+        assert!(csv.contains("\ntotal,3674160,77802,5040,"));
+    }
+
+    #[test]
+    fn quarter_turns() {
+        let table = DistanceTable::build_in(Metric::QTM);
+
+        // the well-known quarter turn metric distribution for the pocket cube
+        assert_eq!(
+            table.histogram(),
+            vec![
+                1, 6, 27, 120, 534, 2256, 8969, 33058, 114149, 360508, 930588, 1350852, 782536,
+                90280, 276
+            ]
+        );
+    }
+}
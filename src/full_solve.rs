@@ -1,20 +1,29 @@
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
-use crate::cube::{Cube, CubeletOrientationArrangement, CubeletPositionArrangement, Facelet};
-use crate::moves::{reversed, Amt, CanMove, Dir, Move};
+use clap::ValueEnum;
+
+use crate::cube::{Cube, CubeletOrientationArrangement, CubeletPositionArrangement};
+use crate::dist_table::{rank_state, DistanceTable};
+use crate::moves::{reversed, CanMove, Move, MoveSet};
 use crate::orr_solve::optimal_solve_orientation;
 use crate::pos_solve::optimal_solve_position;
+use crate::subgroup::Subgroup;
 
 /// Describes which type of heuristic we will use for IDA* search
-// In theory you can pick which heuristic type you want, but in practice there's no reason to,
-// so I just hardcoded it to simplify the CLI. You can always mess with it later.
-#[allow(unused)]
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+#[derive(ValueEnum, Copy, Clone, Eq, PartialEq, Debug, Hash)]
 pub enum HeuristicType {
+    /// Plain iterative deepening
     None,
+    /// The optimal solution length of the positions alone
     Pos,
+    /// The optimal solution length of the orientations alone
     Orr,
+    /// The larger of the position and orientation estimates
+    #[value(name = "both")]
     PosAndOrr,
+    /// The exact distance, from a complete table of every state
+    Table,
 }
 
 fn cache_helper_or_die<Arrangement: CanMove + Clone + std::hash::Hash + Eq + PartialEq>(
@@ -83,7 +92,8 @@ pub trait ShortCircuitCache {
 
     fn depth(&self) -> usize;
 
-    fn load_with_depth(&mut self, depth: usize, f: Facelet, u: Facelet)
+    /// Learns every state within `depth` moves (from the set) of the given solved cube
+    fn load_with_depth(&mut self, depth: usize, solved: Cube, moves: &MoveSet)
     where
         Self: Sized,
     {
@@ -94,6 +104,7 @@ pub trait ShortCircuitCache {
         fn walk<S: ShortCircuitCache>(
             cube: Cube,
             cache: &mut S,
+            moves: &MoveSet,
             running: &mut Vec<Move>,
             max_depth: usize,
         ) {
@@ -112,24 +123,19 @@ pub trait ShortCircuitCache {
                 return;
             }
 
-            for dir in [Dir::R, Dir::F, Dir::U] {
-                if running.last().map(|m| m.dir) == Some(dir) {
+            for m in moves.moves() {
+                if running.last().map(|last| last.dir) == Some(m.dir) {
                     continue;
                 }
 
-                for amt in [Amt::One, Amt::Two, Amt::Rev] {
-                    let m = Move { dir, amt };
-                    let next_cube = cube.clone().apply(m);
-                    running.push(m);
-                    walk(next_cube, cache, running, max_depth);
-                    running.pop();
-                }
+                let next_cube = cube.clone().apply(m);
+                running.push(m);
+                walk(next_cube, cache, moves, running, max_depth);
+                running.pop();
             }
         }
 
-        let cube = Cube::make_solved(f.clone(), u.clone());
-
-        walk(cube.clone(), self, &mut Vec::with_capacity(depth), depth);
+        walk(solved, self, moves, &mut Vec::with_capacity(depth), depth);
     }
 }
 
@@ -143,6 +149,13 @@ impl SimpleShortCircuitCache {
     pub fn cache_size(&self) -> usize {
         self.cache.len()
     }
+
+    /// Every state learned so far, with how far it is from solved
+    pub fn states(&self) -> impl '_ + Iterator<Item = (&Cube, usize)> {
+        self.cache
+            .iter()
+            .map(|(cube, solution)| (cube, solution.len()))
+    }
 }
 
 impl ShortCircuitCache for SimpleShortCircuitCache {
@@ -156,7 +169,7 @@ impl ShortCircuitCache for SimpleShortCircuitCache {
     }
 
     fn known_solution(&self, cube: &Cube) -> Option<&Vec<Move>> {
-        self.cache.get(&cube)
+        self.cache.get(cube)
     }
 }
 
@@ -257,6 +270,44 @@ impl Heuristic for FullHeuristic {
     }
 }
 
+/// Looks up the true distance, so IDA* walks straight to the solution. Building the table is
+/// the whole cost.
+pub struct TableHeuristic {
+    table: DistanceTable,
+}
+
+impl TableHeuristic {
+    pub fn build() -> Self {
+        TableHeuristic {
+            table: DistanceTable::build(),
+        }
+    }
+}
+
+impl Heuristic for TableHeuristic {
+    fn estimated_remaining_cost(
+        &mut self,
+        pos: CubeletPositionArrangement,
+        orr: CubeletOrientationArrangement,
+    ) -> usize {
+        self.estimate_or_die(pos, orr)
+    }
+
+    fn estimate_or_die(
+        &self,
+        pos: CubeletPositionArrangement,
+        orr: CubeletOrientationArrangement,
+    ) -> usize {
+        self.table.state_distance(rank_state(&pos, &orr))
+    }
+}
+
+/// Every pocket cube can be solved in 11 moves, so this is plenty
+const MAX_FUEL: usize = 13;
+
+/// Longer than the diameter of any subgroup of <R,U,F>
+const MAX_RESTRICTED_FUEL: usize = 30;
+
 #[derive(Eq, PartialEq, Debug, Hash, Copy, Clone)]
 enum SolveResult {
     // found a solution
@@ -265,20 +316,30 @@ enum SolveResult {
     Failed,
 }
 
+/// An optimal solution turning only the faces in `moves`; None if they can't solve the cube. The
+/// short circuit cache has to have been loaded with the same moves. The heuristics all count
+/// every move, so they never overestimate the distance with fewer of them.
+///
+/// With fewer moves, working out that a cube can't be solved means searching all the way to
+/// [MAX_RESTRICTED_FUEL], which takes forever, so check it's in the subgroup first (see
+/// [crate::subgroup::Subgroup::contains]).
 pub fn optimal_solve_heuristic<H: Heuristic, S: ShortCircuitCache>(
     cube: Cube,
     heuristic: &mut H,
     short_circuit_cache: &S,
-) -> Vec<Move> {
+    moves: &MoveSet,
+) -> Option<Vec<Move>> {
     if cube.solved() {
-        return vec![];
+        return Some(vec![]);
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn solve<H: Heuristic, S: ShortCircuitCache>(
         cube: Cube,
         pos_arr: CubeletPositionArrangement,
         orr_arr: CubeletOrientationArrangement,
         heuristic: &mut H,
+        moves: &MoveSet,
         running: &mut Vec<Move>,
         max_cost: usize,
         short_circuit_cache: &S,
@@ -306,55 +367,53 @@ pub fn optimal_solve_heuristic<H: Heuristic, S: ShortCircuitCache>(
             heuristic.estimated_remaining_cost(pos_arr.clone(), orr_arr.clone());
         let est_total_cost_now = running.len() + heuristic_cost_now;
 
-        for dir in [Dir::F, Dir::R, Dir::U] {
-            if running.last().map(|m| m.dir) == Some(dir) {
+        for m in moves.moves() {
+            if running.last().map(|last| last.dir) == Some(m.dir) {
                 continue;
             }
 
-            for amt in [Amt::One, Amt::Two, Amt::Rev] {
-                let m = Move { amt, dir };
-                running.push(m);
+            running.push(m);
 
-                let next_cube = cube.clone().apply(m);
+            let next_cube = cube.clone().apply(m);
 
-                if next_cube.solved() {
-                    return SolveResult::Success;
-                }
+            if next_cube.solved() {
+                return SolveResult::Success;
+            }
+
+            let next_pos_arr = pos_arr.clone().apply(m);
+            let next_orr_arr = orr_arr.clone().apply(m);
 
-                let next_pos_arr = pos_arr.clone().apply(m);
-                let next_orr_arr = orr_arr.clone().apply(m);
+            let heuristic_cost =
+                heuristic.estimated_remaining_cost(next_pos_arr.clone(), next_orr_arr.clone());
 
-                let heuristic_cost =
-                    heuristic.estimated_remaining_cost(next_pos_arr.clone(), next_orr_arr.clone());
+            let est_cost = running.len() + heuristic_cost;
 
-                let est_cost = running.len() + heuristic_cost;
+            assert!(
+                est_cost >= est_total_cost_now,
+                "Heuristic cost must not drop too quickly"
+            );
 
-                assert!(
-                    est_cost >= est_total_cost_now,
-                    "Heuristic cost must not drop too quickly"
+            if est_cost <= max_cost {
+                // if we have enough gas to get to the next node, try it out
+                let iterate_result = solve(
+                    next_cube,
+                    next_pos_arr,
+                    next_orr_arr,
+                    heuristic,
+                    moves,
+                    running,
+                    max_cost,
+                    short_circuit_cache,
                 );
 
-                if est_cost <= max_cost {
-                    // if we have enough gas to get to the next node, try it out
-                    let iterate_result = solve(
-                        next_cube,
-                        next_pos_arr,
-                        next_orr_arr,
-                        heuristic,
-                        running,
-                        max_cost,
-                        short_circuit_cache,
-                    );
-
-                    match iterate_result {
-                        // immediately return, so the "running" vec has all the stuff it needs
-                        SolveResult::Success => return SolveResult::Success,
-                        SolveResult::Failed => {}
-                    }
+                match iterate_result {
+                    // immediately return, so the "running" vec has all the stuff it needs
+                    SolveResult::Success => return SolveResult::Success,
+                    SolveResult::Failed => {}
                 }
-
-                running.pop();
             }
+
+            running.pop();
         }
 
         SolveResult::Failed
@@ -363,9 +422,10 @@ pub fn optimal_solve_heuristic<H: Heuristic, S: ShortCircuitCache>(
     fn solve_with_heuristic<H: Heuristic, S: ShortCircuitCache>(
         cube: Cube,
         heuristic: &mut H,
+        moves: &MoveSet,
         max_fuel: usize,
         short_circuit_cache: &S,
-    ) -> Vec<Move> {
+    ) -> Option<Vec<Move>> {
         let pos_arr = cube.clone().make_pos_arr_from_dlb();
         let orr_arr = cube.clone().make_orr_arr_from_dlb();
 
@@ -379,54 +439,163 @@ pub fn optimal_solve_heuristic<H: Heuristic, S: ShortCircuitCache>(
                 pos_arr.clone(),
                 orr_arr.clone(),
                 heuristic,
+                moves,
                 &mut running,
                 starting_fuel,
                 short_circuit_cache,
             );
 
             if sr == SolveResult::Success {
-                return running;
+                return Some(running);
             }
 
             starting_fuel += 1;
         }
 
-        unreachable!("Should have found a solution!")
+        None
     }
 
-    const MAX_FUEL: usize = 13;
+    let max_fuel = if moves.is_all() {
+        MAX_FUEL
+    } else {
+        MAX_RESTRICTED_FUEL
+    };
 
-    solve_with_heuristic(cube, heuristic, MAX_FUEL, short_circuit_cache)
+    solve_with_heuristic(cube, heuristic, moves, max_fuel, short_circuit_cache)
 }
 
-pub fn optimal_solve(cube: Cube, heuristic_type: HeuristicType) -> Vec<Move> {
-    let mut short_circuit_cache = SimpleShortCircuitCache::default();
-    let des = cube.clone().make_desired_from_dlb();
+/// Every sequence of moves from the set, at most `max_length` long, which takes `start` to
+/// somewhere `distance` says is 0 away; shortest first, and up to `limit` of them. This is the
+/// same iterative deepening as [optimal_solve_heuristic], except it keeps going after the first
+/// solution, and only turns the faces in `moves`. The distance must never be more than the real
+/// number of moves needed (using only the set), or some solutions will be missed.
+pub fn all_solutions<T: CanMove + Clone>(
+    start: T,
+    moves: &MoveSet,
+    max_length: usize,
+    limit: usize,
+    distance: impl Fn(&T) -> usize,
+) -> Vec<Vec<Move>> {
+    fn walk<T: CanMove + Clone>(
+        state: T,
+        moves: &MoveSet,
+        running: &mut Vec<Move>,
+        fuel: usize,
+        limit: usize,
+        distance: &impl Fn(&T) -> usize,
+        out: &mut Vec<Vec<Move>>,
+    ) {
+        let remaining = distance(&state);
+
+        if running.len() == fuel {
+            if remaining == 0 {
+                out.push(running.clone());
+            }
+            return;
+        } else if running.len() + remaining > fuel {
+            return;
+        }
 
-    // right now, the heuristics and the short-circuit cache interact badly, so we disable
-    // one or the other
-    let short_circuit_depth = match heuristic_type {
-        HeuristicType::None => 5,
-        _ => 5,
-    };
+        for m in moves.moves() {
+            if running.last().map(|last| last.dir) == Some(m.dir) {
+                continue;
+            }
+
+            running.push(m);
+            walk(
+                state.clone().apply(m),
+                moves,
+                running,
+                fuel,
+                limit,
+                distance,
+                out,
+            );
+            running.pop();
+
+            if out.len() >= limit {
+                return;
+            }
+        }
+    }
 
-    println!("Precomputing cache of depth {}", short_circuit_depth);
-    short_circuit_cache.load_with_depth(short_circuit_depth, des.f, des.u);
+    let mut out = Vec::new();
+
+    for fuel in 0..=max_length {
+        walk(
+            start.clone(),
+            moves,
+            &mut Vec::with_capacity(fuel),
+            fuel,
+            limit,
+            &distance,
+            &mut out,
+        );
 
-    match heuristic_type {
-        HeuristicType::None => {
-            optimal_solve_heuristic(cube, &mut NoHeuristic::default(), &short_circuit_cache)
+        if out.len() >= limit {
+            out.truncate(limit);
+            break;
         }
+    }
+
+    out
+}
+
+/// An optimal solution, along with what it cost to find
+pub struct SolveReport {
+    pub solution: Vec<Move>,
+    pub cache_depth: usize,
+    pub cache_size: usize,
+    pub cache_time: Duration,
+    pub search_time: Duration,
+}
+
+/// An optimal solution turning only the faces in `moves`, along with what it cost to find; None
+/// if they can't solve the cube
+pub fn optimal_solve_report(
+    cube: Cube,
+    heuristic_type: HeuristicType,
+    cache_depth: usize,
+    moves: &MoveSet,
+) -> Option<SolveReport> {
+    // without every move, finding out there's no solution by searching takes forever, but
+    // walking the whole subgroup is quick
+    if !moves.is_all() && !Subgroup::explore(moves.clone()).contains(&cube) {
+        return None;
+    }
+
+    let mut short_circuit_cache = SimpleShortCircuitCache::default();
+    let des = cube.clone().make_desired_from_dlb();
+
+    let cache_start = Instant::now();
+    short_circuit_cache.load_with_depth(cache_depth, des.solved_cube(), moves);
+    let cache_time = cache_start.elapsed();
+
+    let search_start = Instant::now();
+    let cache = &short_circuit_cache;
+    let solution = match heuristic_type {
+        HeuristicType::None => optimal_solve_heuristic(cube, &mut NoHeuristic, cache, moves),
         HeuristicType::Pos => {
-            optimal_solve_heuristic(cube, &mut PosHeuristic::default(), &short_circuit_cache)
+            optimal_solve_heuristic(cube, &mut PosHeuristic::default(), cache, moves)
         }
         HeuristicType::Orr => {
-            optimal_solve_heuristic(cube, &mut OrrHeuristic::default(), &short_circuit_cache)
+            optimal_solve_heuristic(cube, &mut OrrHeuristic::default(), cache, moves)
         }
         HeuristicType::PosAndOrr => {
-            optimal_solve_heuristic(cube, &mut FullHeuristic::default(), &short_circuit_cache)
+            optimal_solve_heuristic(cube, &mut FullHeuristic::default(), cache, moves)
         }
-    }
+        HeuristicType::Table => {
+            optimal_solve_heuristic(cube, &mut TableHeuristic::build(), cache, moves)
+        }
+    }?;
+
+    Some(SolveReport {
+        solution,
+        cache_depth,
+        cache_size: short_circuit_cache.cache_size(),
+        cache_time,
+        search_time: search_start.elapsed(),
+    })
 }
 
 #[cfg(test)]
@@ -438,7 +607,7 @@ mod random_tests {
     use super::*;
 
     // Exhibits the problem, though I don't know why. This has a solution of length 9.
-    const PROBLEM_CHILD: &'static str = "F2 R' F' F2 U2 R2 F R U' R U2 R' L";
+    const PROBLEM_CHILD: &str = "F2 R' F' F2 U2 R2 F R U' R U2 R' L";
 
     fn do_test(input: &str, ht: HeuristicType, exp_length: usize) {
         let moves = parse_line(input).unwrap();
@@ -450,7 +619,9 @@ mod random_tests {
 
         let start = start;
 
-        let solution = optimal_solve(start.clone(), ht);
+        let solution = optimal_solve_report(start.clone(), ht, 5, &MoveSet::all())
+            .unwrap()
+            .solution;
 
         println!(
             "Given scramble \"{}\", got solution \"{}\"",
@@ -493,4 +664,9 @@ mod random_tests {
     fn test_sample_full_heuristic() {
         do_test(PROBLEM_CHILD, HeuristicType::PosAndOrr, 9);
     }
+
+    #[test]
+    fn test_sample_table_heuristic() {
+        do_test(PROBLEM_CHILD, HeuristicType::Table, 9);
+    }
 }
@@ -0,0 +1,439 @@
+//! Generating algorithms for a case, in the style of Cube Explorer: every sequence up to some
+//! length which solves it, maybe only turning some of the faces, and maybe with a U turn allowed
+//! before and after. The case is either a whole state, or a masked cube where the unknown
+//! facelets don't matter (so for an OLL case, only the U and D colors are given).
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::color_scheme::ColorScheme;
+use crate::cube::{Cube, Facelet, CORNER_FACELETS};
+use crate::dist_table::{rank_cube, DistanceTable};
+use crate::full_solve::all_solutions;
+use crate::masked::MaskedCube;
+use crate::moves::{nice_write, Amt, CanMove, Dir, Move, MoveSet};
+use crate::symmetry::rotations;
+
+/// Past this many, don't bother finding any more algorithms to rank
+const MAX_FOUND: usize = 100_000;
+
+pub enum Case {
+    State(Cube),
+    Mask(MaskedCube),
+}
+
+impl Case {
+    /// A masked cube with nothing unknown is really just a state
+    pub fn from_masked(masked: MaskedCube) -> Self {
+        if masked.is_known() {
+            Case::State(Cube::from_facelets(
+                masked.facelets.map(|f| f.expect("Every facelet is known")),
+            ))
+        } else {
+            Case::Mask(masked)
+        }
+    }
+
+    /// What's wrong with asking for this, if anything
+    pub fn check(&self, scheme: &ColorScheme) -> Result<(), String> {
+        match self {
+            Case::State(_) => Ok(()),
+            Case::Mask(masked) => mask_goal(masked, scheme).map(|_| ()).ok_or_else(|| {
+                "The known facelets of the DLB corner don't fit any way of holding a solved cube"
+                    .to_string()
+            }),
+        }
+    }
+}
+
+/// The solved cube a mask is aiming for: like a whole state, it's held however its DLB corner
+/// says, since the moves never turn that corner. If only some of the corner is known, then the
+/// scheme's usual way of holding the cube comes first.
+fn mask_goal(masked: &MaskedCube, scheme: &ColorScheme) -> Option<[Facelet; 24]> {
+    let solved = scheme.solved_cube();
+
+    rotations()
+        .iter()
+        .map(|rotation| rotation.turn(&solved).to_facelets())
+        .find(|goal| {
+            CORNER_FACELETS[0]
+                .iter()
+                .all(|&i| masked.facelets[i].as_ref().is_none_or(|f| *f == goal[i]))
+        })
+}
+
+pub struct Options {
+    pub moves: MoveSet,
+    pub max_length: usize,
+    /// Allow a U turn before the algorithm
+    pub pre_auf: bool,
+    /// Allow a U turn after the algorithm
+    pub post_auf: bool,
+}
+
+pub struct Generated {
+    pub pre: Option<Move>,
+    pub moves: Vec<Move>,
+    pub post: Option<Move>,
+    pub score: f64,
+}
+
+impl Generated {
+    /// The algorithm, with any U turns around it in brackets, like "(U) R U2 R' (U')"
+    pub fn write(&self) -> String {
+        let mut parts = Vec::new();
+
+        if let Some(pre) = self.pre {
+            parts.push(format!("({})", pre));
+        }
+        if !self.moves.is_empty() {
+            parts.push(nice_write(&self.moves));
+        }
+        if let Some(post) = self.post {
+            parts.push(format!("({})", post));
+        }
+
+        parts.join(" ")
+    }
+}
+
+/// This many quarter turns of U, if that does anything
+fn u_turn(count: usize) -> Option<Move> {
+    Amt::from_quarter_turns(count).map(|amt| Move { dir: Dir::U, amt })
+}
+
+fn aufs(allowed: bool) -> Vec<Option<Move>> {
+    if allowed {
+        (0..4).map(u_turn).collect()
+    } else {
+        vec![None]
+    }
+}
+
+/// How awkward the algorithm is to do, roughly, for right handed speedsolving; lower is better.
+/// R and U turns are the easiest, F turns need more of a reach, half turns take longer, and
+/// every switch between turning R and turning F costs a regrip.
+pub fn ergonomic_score(moves: &[Move]) -> f64 {
+    let turns: f64 = moves
+        .iter()
+        .map(|m| {
+            let face = match m.dir {
+                Dir::U => 0.8,
+                Dir::R => 1.0,
+                Dir::F => 1.5,
+            };
+            match m.amt {
+                Amt::Two => face * 1.5,
+                _ => face,
+            }
+        })
+        .sum();
+
+    let mut regrips = 0;
+    let mut last_side = None;
+    for m in moves {
+        if m.dir != Dir::U {
+            if last_side.is_some_and(|side| side != m.dir) {
+                regrips += 1;
+            }
+            last_side = Some(m.dir);
+        }
+    }
+
+    turns + 0.5 * regrips as f64
+}
+
+/// Whether every known facelet is where it belongs, in the scheme's solved cube
+fn in_place(masked: &MaskedCube, solved: &[Facelet; 24]) -> bool {
+    masked
+        .facelets
+        .iter()
+        .zip(solved.iter())
+        .all(|(have, want)| have.as_ref().is_none_or(|have| have == want))
+}
+
+/// How far every masked cube reachable from the starts is from having its known facelets in
+/// place (after one of the `ends`). There's nothing like the distance table for masked cubes,
+/// but the fewer facelets are known, the fewer different masked cubes there are, so this walks
+/// all of them.
+fn mask_distances(
+    starts: &[MaskedCube],
+    moves: &MoveSet,
+    ends: &[Option<Move>],
+    solved: &[Facelet; 24],
+) -> HashMap<MaskedCube, usize> {
+    let reached = |masked: &MaskedCube| {
+        ends.iter()
+            .any(|end| in_place(&masked.clone().apply_many(end.as_slice()), solved))
+    };
+
+    // first, everything there is
+    let mut seen: Vec<MaskedCube> = Vec::new();
+    let mut index: HashMap<MaskedCube, usize> = HashMap::new();
+    for start in starts {
+        if !index.contains_key(start) {
+            index.insert(start.clone(), seen.len());
+            seen.push(start.clone());
+        }
+    }
+
+    let mut next = 0;
+    while next < seen.len() {
+        for m in moves.moves() {
+            let neighbor = seen[next].clone().apply(m);
+            if !index.contains_key(&neighbor) {
+                index.insert(neighbor.clone(), seen.len());
+                seen.push(neighbor);
+            }
+        }
+        next += 1;
+    }
+
+    // then walk back out from the ones already done; the moves undo each other, so the distance
+    // from them is the distance to them
+    let mut distances: HashMap<MaskedCube, usize> = HashMap::new();
+    let mut queue: VecDeque<MaskedCube> = VecDeque::new();
+    for masked in seen.into_iter().filter(|m| reached(m)) {
+        distances.insert(masked.clone(), 0);
+        queue.push_back(masked);
+    }
+
+    while let Some(masked) = queue.pop_front() {
+        let distance = distances[&masked];
+        for m in moves.moves() {
+            let neighbor = masked.clone().apply(m);
+            if !distances.contains_key(&neighbor) {
+                distances.insert(neighbor.clone(), distance + 1);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    distances
+}
+
+/// Every algorithm of up to `options.max_length` moves (not counting the U turns around it) which
+/// solves the case, easiest first by [ergonomic_score]; the case should pass [Case::check]
+pub fn generate(case: &Case, scheme: &ColorScheme, options: &Options) -> Vec<Generated> {
+    let pres = aufs(options.pre_auf);
+    let posts = aufs(options.post_auf);
+
+    // (the U turn before, the algorithm, the U turn after)
+    let mut found: Vec<(Option<Move>, Vec<Move>, Option<Move>)> = Vec::new();
+
+    match case {
+        Case::State(cube) => {
+            // finishing with a U turn means stopping a U turn away from solved
+            let solved = cube.make_desired_from_dlb().solved_cube();
+            let goals: Vec<usize> = posts
+                .iter()
+                .map(|post| rank_cube(&solved.clone().apply_many(post.as_slice())))
+                .collect();
+            let table = DistanceTable::build_restricted(&options.moves, &goals);
+
+            for pre in &pres {
+                let start = cube.clone().apply_many(pre.as_slice());
+                let algs = all_solutions(
+                    start.clone(),
+                    &options.moves,
+                    options.max_length,
+                    MAX_FOUND,
+                    |c: &Cube| table.distance(c),
+                );
+
+                for moves in algs {
+                    let done = start.clone().apply_many(&moves);
+                    let post = posts
+                        .iter()
+                        .find(|post| done.clone().apply_many(post.as_slice()).solved())
+                        .expect("The search only finds algorithms which solve the case");
+                    found.push((*pre, moves, *post));
+                }
+            }
+        }
+        Case::Mask(masked) => {
+            let solved = mask_goal(masked, scheme).expect("Checked by Case::check");
+            let starts: Vec<MaskedCube> = pres
+                .iter()
+                .map(|pre| masked.clone().apply_many(pre.as_slice()))
+                .collect();
+            let distances = mask_distances(&starts, &options.moves, &posts, &solved);
+
+            for (pre, start) in pres.iter().zip(starts) {
+                let algs = all_solutions(
+                    start.clone(),
+                    &options.moves,
+                    options.max_length,
+                    MAX_FOUND,
+                    |m: &MaskedCube| distances.get(m).copied().unwrap_or(usize::MAX / 2),
+                );
+
+                for moves in algs {
+                    let done = start.clone().apply_many(&moves);
+                    let post = posts
+                        .iter()
+                        .find(|post| in_place(&done.clone().apply_many(post.as_slice()), &solved))
+                        .expect("The search only finds algorithms which solve the case");
+                    found.push((*pre, moves, *post));
+                }
+            }
+        }
+    }
+
+    let mut out: Vec<Generated> = found
+        .into_iter()
+        // a U turn at either end would just be a different AUF
+        .filter(|(_, moves, _)| {
+            !(options.pre_auf && moves.first().is_some_and(|m| m.dir == Dir::U))
+        })
+        .filter(|(_, moves, _)| {
+            !(options.post_auf && moves.last().is_some_and(|m| m.dir == Dir::U))
+        })
+        .map(|(pre, moves, post)| Generated {
+            pre,
+            score: ergonomic_score(&moves),
+            moves,
+            post,
+        })
+        .collect();
+
+    out.sort_by(|a, b| {
+        a.score
+            .total_cmp(&b.score)
+            .then(a.moves.len().cmp(&b.moves.len()))
+    });
+
+    out
+}
+
+#[cfg(test)]
+mod generate_tests {
+    use crate::moves::{flipped_full, CanFullMove};
+    use crate::setup::{parse_line, parse_masked};
+
+    use super::*;
+
+    const SUNE: &str = "R U R' U R U2 R'";
+
+    fn sune_case(scheme: &ColorScheme) -> Cube {
+        scheme
+            .solved_cube()
+            .apply_many_full(&flipped_full(&parse_line(SUNE).unwrap()))
+    }
+
+    #[test]
+    fn algorithms_for_a_state() {
+        let scheme = ColorScheme::western();
+        let cube = sune_case(&scheme);
+
+        let options = Options {
+            moves: "<R,U>".parse().unwrap(),
+            max_length: 7,
+            pre_auf: false,
+            post_auf: false,
+        };
+        let found = generate(&Case::State(cube.clone()), &scheme, &options);
+
+        assert!(found.iter().any(|g| nice_write(&g.moves) == SUNE));
+        for g in &found {
+            assert!(g.moves.iter().all(|m| m.dir != Dir::F));
+            assert!(cube.clone().apply_many(&g.moves).solved());
+        }
+
+        // an AUF either side is fine too, but never a U turn at the ends of the algorithm itself
+        let cube = cube.apply_many_full(&parse_line("U").unwrap());
+        let options = Options {
+            pre_auf: true,
+            post_auf: true,
+            ..options
+        };
+        let found = generate(&Case::State(cube.clone()), &scheme, &options);
+        assert!(found.iter().any(|g| nice_write(&g.moves) == SUNE));
+        for g in &found {
+            assert_ne!(g.moves.first().map(|m| m.dir), Some(Dir::U));
+            let done = cube
+                .clone()
+                .apply_many(g.pre.as_slice())
+                .apply_many(&g.moves)
+                .apply_many(g.post.as_slice());
+            assert!(done.solved());
+        }
+    }
+
+    #[test]
+    fn algorithms_for_a_mask() {
+        let scheme = ColorScheme::western();
+        let cube = sune_case(&scheme);
+
+        // only the U and D colors count, as for orienting the last layer
+        let facelets: String = cube
+            .to_facelets()
+            .iter()
+            .map(|f| match f {
+                Facelet::White | Facelet::Yellow => f.letter(),
+                _ => '?',
+            })
+            .collect();
+        let masked = parse_masked(&facelets, &scheme).unwrap();
+
+        let options = Options {
+            moves: "<R,U>".parse().unwrap(),
+            max_length: 7,
+            pre_auf: true,
+            post_auf: false,
+        };
+        let found = generate(&Case::from_masked(masked.clone()), &scheme, &options);
+
+        assert!(found.iter().any(|g| nice_write(&g.moves) == SUNE));
+
+        let solved = scheme.solved_cube().to_facelets();
+        for g in &found {
+            let done = masked
+                .clone()
+                .apply_many(g.pre.as_slice())
+                .apply_many(&g.moves);
+            assert!(in_place(&done, &solved));
+        }
+
+        // easiest first
+        assert!(found.windows(2).all(|w| w[0].score <= w[1].score));
+    }
+
+    #[test]
+    fn masks_held_another_way() {
+        let scheme = ColorScheme::western();
+        let y = rotations().into_iter().find(|r| r.name() == "y").unwrap();
+        let cube = y.turn(&sune_case(&scheme));
+
+        // the side facelets of the last layer are unknown, but DLB is all there, and says the
+        // cube's been turned
+        let mut masked = MaskedCube::from_cube(&cube);
+        for i in [4, 5, 8, 9, 16, 17, 20, 21] {
+            masked.facelets[i] = None;
+        }
+        let case = Case::from_masked(masked.clone());
+        assert!(case.check(&scheme).is_ok());
+
+        let options = Options {
+            moves: "<R,U>".parse().unwrap(),
+            max_length: 7,
+            pre_auf: true,
+            post_auf: true,
+        };
+        let found = generate(&case, &scheme, &options);
+        assert!(found.iter().any(|g| nice_write(&g.moves) == SUNE));
+
+        let solved = y.turn(&scheme.solved_cube()).to_facelets();
+        for g in &found {
+            let done = masked
+                .clone()
+                .apply_many(g.pre.as_slice())
+                .apply_many(&g.moves)
+                .apply_many(g.post.as_slice());
+            assert!(in_place(&done, &solved));
+        }
+
+        // no way of holding the cube has two white facelets on one corner
+        masked.facelets[CORNER_FACELETS[0][1]] = masked.facelets[CORNER_FACELETS[0][0]].clone();
+        assert!(Case::from_masked(masked).check(&scheme).is_err());
+    }
+}
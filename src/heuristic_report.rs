@@ -0,0 +1,236 @@
+//! How good the IDA* heuristics are, measured against the distance table: how far under the true
+//! distance each one guesses, and so how many nodes a search guided by it should expect to visit.
+//!
+//! The node counts are the usual prediction for IDA* (Korf, Reid and Edelkamp): a search to depth
+//! `d` visits about `sum over i of N(i) * P(d - i)` nodes, where `N(i)` is how many move sequences
+//! of length `i` there are, and `P(h)` is the fraction of states the heuristic puts within `h`.
+
+use rand::seq::index::sample;
+use serde::Serialize;
+
+use crate::cube::{CubeletOrientationArrangement, CubeletPositionArrangement};
+use crate::dist_table::{
+    unrank_orr, unrank_pos, DistanceTable, NUM_ORIENTATIONS, NUM_POSITIONS, NUM_STATES,
+};
+use crate::full_solve::{FullHeuristic, Heuristic, OrrHeuristic, PosHeuristic};
+
+#[derive(Serialize, Debug)]
+pub struct HeuristicStats {
+    /// As in the --heuristic option
+    pub name: &'static str,
+    /// How far under the true distance the estimate is, on average
+    pub average_gap: f64,
+    /// The fraction of states where the estimate is the true distance
+    pub exact: f64,
+    /// How many states have each estimate, indexed by true distance and then estimate
+    pub pairs: Vec<Vec<usize>>,
+    /// Indexed by the depth of the search
+    pub predicted_nodes: Vec<f64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct HeuristicReport {
+    /// How many states were compared
+    pub states: usize,
+    /// Whether those were every state, or a random sample
+    pub sampled: bool,
+    /// What a search with no heuristic at all visits, indexed by depth
+    pub brute_force_nodes: Vec<f64>,
+    pub heuristics: Vec<HeuristicStats>,
+}
+
+/// The heuristics look up what they've already solved with [Heuristic::estimate_or_die], so
+/// solve every position and every orientation first
+fn warm_up<H: Heuristic>(heuristic: &mut H) {
+    let solved_pos = CubeletPositionArrangement::make_solved();
+    let solved_orr = CubeletOrientationArrangement::make_solved();
+
+    for rank in 0..NUM_POSITIONS {
+        heuristic.estimated_remaining_cost(unrank_pos(rank), solved_orr.clone());
+    }
+    for rank in 0..NUM_ORIENTATIONS {
+        heuristic.estimated_remaining_cost(solved_pos.clone(), unrank_orr(rank));
+    }
+}
+
+/// How many move sequences of each length up to `max` IDA* tries: any of the 9 moves first, and
+/// then any of the 6 which don't turn the same face again
+fn sequences_by_length(max: usize) -> Vec<f64> {
+    (0..=max)
+        .map(|i| match i {
+            0 => 1.0,
+            _ => 9.0 * 6f64.powi(i as i32 - 1),
+        })
+        .collect()
+}
+
+/// Tallies up (true distance, estimate) pairs
+struct Tally {
+    name: &'static str,
+    pairs: Vec<Vec<usize>>,
+}
+
+impl Tally {
+    fn new(name: &'static str, max_distance: usize) -> Self {
+        Tally {
+            name,
+            pairs: vec![vec![0; max_distance + 1]; max_distance + 1],
+        }
+    }
+
+    fn add(&mut self, distance: usize, estimate: usize) {
+        assert!(
+            estimate <= distance,
+            "The {} heuristic overestimated a state {} moves away as {}",
+            self.name,
+            distance,
+            estimate
+        );
+        self.pairs[distance][estimate] += 1;
+    }
+
+    fn stats(self, sequences: &[f64]) -> HeuristicStats {
+        let total: usize = self.pairs.iter().flatten().sum();
+
+        let mut gap = 0;
+        let mut exact = 0;
+        let mut by_estimate = vec![0; self.pairs.len()];
+        for (distance, row) in self.pairs.iter().enumerate() {
+            // nothing is ever estimated past its true distance
+            for (estimate, &count) in row.iter().enumerate().take(distance + 1) {
+                gap += (distance - estimate) * count;
+                by_estimate[estimate] += count;
+                if estimate == distance {
+                    exact += count;
+                }
+            }
+        }
+
+        // the fraction of states with an estimate of at most h
+        let mut within = Vec::with_capacity(by_estimate.len());
+        let mut running = 0;
+        for count in by_estimate {
+            running += count;
+            within.push(running as f64 / total as f64);
+        }
+
+        let predicted_nodes = (0..sequences.len())
+            .map(|depth| {
+                (0..=depth)
+                    .map(|i| sequences[i] * within[(depth - i).min(within.len() - 1)])
+                    .sum()
+            })
+            .collect();
+
+        // the estimates are whole numbers of moves, so trimming the columns nobody got is tidier
+        let widest = self
+            .pairs
+            .iter()
+            .filter_map(|row| row.iter().rposition(|&count| count > 0))
+            .max()
+            .unwrap_or(0);
+        let pairs = self
+            .pairs
+            .into_iter()
+            .map(|row| row[..=widest].to_vec())
+            .collect();
+
+        HeuristicStats {
+            name: self.name,
+            average_gap: gap as f64 / total as f64,
+            exact: exact as f64 / total as f64,
+            pairs,
+            predicted_nodes,
+        }
+    }
+}
+
+/// Compares the orientation, position and combined heuristics against the true distance, for
+/// every state, or `sample_size` random ones
+pub fn heuristic_report(table: &DistanceTable, sample_size: Option<usize>) -> HeuristicReport {
+    let mut orr = OrrHeuristic::default();
+    let mut pos = PosHeuristic::default();
+    let mut full = FullHeuristic::default();
+    warm_up(&mut orr);
+    warm_up(&mut pos);
+    warm_up(&mut full);
+
+    let states: Vec<usize> = match sample_size {
+        Some(size) if size < NUM_STATES => {
+            sample(&mut rand::thread_rng(), NUM_STATES, size).into_vec()
+        }
+        _ => (0..NUM_STATES).collect(),
+    };
+
+    let max_distance = table.max_distance();
+    let mut tallies = [
+        Tally::new("orr", max_distance),
+        Tally::new("pos", max_distance),
+        Tally::new("both", max_distance),
+    ];
+
+    for &state in &states {
+        let distance = table.state_distance(state);
+        let p = unrank_pos(state / NUM_ORIENTATIONS);
+        let o = unrank_orr(state % NUM_ORIENTATIONS);
+
+        tallies[0].add(distance, orr.estimate_or_die(p.clone(), o.clone()));
+        tallies[1].add(distance, pos.estimate_or_die(p.clone(), o.clone()));
+        tallies[2].add(distance, full.estimate_or_die(p, o));
+    }
+
+    let sequences = sequences_by_length(max_distance);
+    let brute_force_nodes: Vec<f64> = sequences
+        .iter()
+        .scan(0.0, |total, &n| {
+            *total += n;
+            Some(*total)
+        })
+        .collect();
+
+    HeuristicReport {
+        states: states.len(),
+        sampled: states.len() < NUM_STATES,
+        brute_force_nodes,
+        heuristics: tallies.into_iter().map(|t| t.stats(&sequences)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod heuristic_report_tests {
+    use super::*;
+
+    #[test]
+    fn sequence_counts() {
+        assert_eq!(sequences_by_length(3), vec![1.0, 9.0, 54.0, 324.0]);
+    }
+
+    #[test]
+    fn sampled_report() {
+        let table = DistanceTable::build();
+        let report = heuristic_report(&table, Some(5000));
+
+        assert_eq!(report.states, 5000);
+        assert!(report.sampled);
+
+        let [orr, pos, full] = &report.heuristics[..] else {
+            panic!("Expected three heuristics");
+        };
+
+        for stats in &report.heuristics {
+            let counted: usize = stats.pairs.iter().flatten().sum();
+            assert_eq!(counted, 5000);
+            assert!(stats.average_gap > 0.0);
+
+            // a heuristic can only ever save nodes
+            for (predicted, brute) in stats.predicted_nodes.iter().zip(&report.brute_force_nodes) {
+                assert!(predicted <= brute);
+            }
+        }
+
+        // taking the larger of the two is never worse than either
+        assert!(full.average_gap <= orr.average_gap);
+        assert!(full.average_gap <= pos.average_gap);
+        assert!(full.predicted_nodes[11] <= orr.predicted_nodes[11]);
+    }
+}
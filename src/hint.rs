@@ -0,0 +1,118 @@
+//! What every possible next move does to the distance from solved, for coaching
+
+use serde::Serialize;
+
+use crate::cube::Cube;
+use crate::dist_table::DistanceTable;
+use crate::moves::{CanMove, Move, ALL_MOVES};
+use crate::output::move_strings;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Grade {
+    Better,
+    Same,
+    Worse,
+}
+
+pub struct MoveHint {
+    pub m: Move,
+    /// The optimal distance after doing the move
+    pub distance: usize,
+    pub grade: Grade,
+}
+
+impl MoveHint {
+    /// Whether the move starts some optimal solution
+    pub fn optimal(&self) -> bool {
+        self.grade == Grade::Better
+    }
+}
+
+/// Every move (with DLB fixed), with where it leaves you, best first
+pub fn hints(cube: &Cube, table: &DistanceTable) -> Vec<MoveHint> {
+    let now = table.distance(cube);
+
+    let mut out: Vec<MoveHint> = ALL_MOVES
+        .iter()
+        .map(|&m| {
+            let distance = table.distance(&cube.clone().apply(m));
+            let grade = match distance.cmp(&now) {
+                std::cmp::Ordering::Less => Grade::Better,
+                std::cmp::Ordering::Equal => Grade::Same,
+                std::cmp::Ordering::Greater => Grade::Worse,
+            };
+
+            MoveHint { m, distance, grade }
+        })
+        .collect();
+
+    // stable, so ties stay in the usual R U F order
+    out.sort_by_key(|hint| hint.distance);
+    out
+}
+
+#[derive(Serialize, Debug)]
+pub struct HintOutput {
+    pub input: String,
+    pub distance: usize,
+    pub better: Vec<String>,
+    pub same: Vec<String>,
+    pub worse: Vec<String>,
+    /// The moves which start some optimal solution
+    pub optimal: Vec<String>,
+}
+
+impl HintOutput {
+    pub fn new(input: &str, distance: usize, hints: &[MoveHint]) -> Self {
+        let moves = |keep: &dyn Fn(&MoveHint) -> bool| -> Vec<String> {
+            let picked: Vec<Move> = hints.iter().filter(|h| keep(h)).map(|h| h.m).collect();
+            move_strings(&picked)
+        };
+
+        HintOutput {
+            input: input.to_string(),
+            distance,
+            better: moves(&|h| h.grade == Grade::Better),
+            same: moves(&|h| h.grade == Grade::Same),
+            worse: moves(&|h| h.grade == Grade::Worse),
+            optimal: moves(&|h| h.optimal()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod hint_tests {
+    use crate::color_scheme::ColorScheme;
+    use crate::moves::CanFullMove;
+    use crate::setup::parse_line;
+
+    use super::*;
+
+    #[test]
+    fn grades_add_up() {
+        let table = DistanceTable::build();
+        let cube = ColorScheme::western()
+            .solved_cube()
+            .apply_many_full(&parse_line("R U2 F'").unwrap());
+
+        let hints = hints(&cube, &table);
+        assert_eq!(hints.len(), 9);
+
+        // only undoing the last move helps; in the half turn metric, a move changes the distance
+        // by at most one
+        let better: Vec<String> = hints
+            .iter()
+            .filter(|h| h.optimal())
+            .map(|h| h.m.to_string())
+            .collect();
+        assert_eq!(better, vec!["F"]);
+
+        for hint in &hints {
+            assert!(hint.distance.abs_diff(3) <= 1);
+        }
+
+        // the other two F moves don't get anywhere
+        let same = hints.iter().filter(|h| h.grade == Grade::Same).count();
+        assert_eq!(same, 2);
+    }
+}
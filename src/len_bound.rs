@@ -2,59 +2,144 @@
 //! "God's number").
 
 use std::collections::VecDeque;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crate::cube::{
     Cube, CubeletOrientation, CubeletOrientationArrangement, CubeletPos,
     CubeletPositionArrangement, Facelet, ALL_CUBIES,
 };
+use crate::dist_table::rank_cube;
 use crate::full_solve::{
-    optimal_solve_heuristic, FullHeuristic, Heuristic, ShortCircuitCache, SimpleShortCircuitCache,
+    optimal_solve_heuristic, FullHeuristic, Heuristic, HeuristicType, NoHeuristic, OrrHeuristic,
+    PosHeuristic, ShortCircuitCache, SimpleShortCircuitCache, TableHeuristic,
 };
+use crate::moves::MoveSet;
 use crate::scramble::put_cubie;
+use crate::symmetry::{all_symmetries, canonical_states};
+
+/// The optimal-path-bound, along with what it cost to derive
+pub struct LenBoundReport {
+    pub length_bound: usize,
+    pub cache_depth: usize,
+    pub cache_size: usize,
+    /// How many states were actually solved; with symmetry, only one from each class
+    pub states_solved: usize,
+    pub heuristic_time: Duration,
+    pub cache_time: Duration,
+    pub search_time: Duration,
+}
 
-pub fn compute_len_bound() -> usize {
+/// With `symmetry`, only the least state of each class of symmetric states is solved (see
+/// [crate::symmetry::canonicalize]), since they're all the same distance from solved
+pub fn compute_len_bound(
+    heuristic_type: HeuristicType,
+    cache_depth: usize,
+    symmetry: bool,
+) -> LenBoundReport {
     // basically we're going to iterate through every meaningfully different setup
     // and compute their optimal solution length
 
-    println!("By symmetry, we can assume the DLB corner is white/blue/red, with white on bottom");
+    // progress goes to stderr, so stdout only has the result in it
+    eprintln!("By symmetry, we can assume the DLB corner is white/blue/red, with white on bottom");
+
+    let canonical = if symmetry {
+        eprintln!("Only solving one state from each class of symmetric states");
+        let start = Instant::now();
+        let canonical = canonical_states(&all_symmetries());
+        eprintln!(
+            "Found the least state of each class in {:?}",
+            start.elapsed()
+        );
+        Some(canonical)
+    } else {
+        None
+    };
+    let canonical = canonical.as_deref();
+
+    // the lookup heuristics are filled in up front, so the search itself is all cache hits
+    let heuristic_start = Instant::now();
 
-    let mut heuristic = FullHeuristic::default();
+    match heuristic_type {
+        HeuristicType::None => search_all(NoHeuristic, heuristic_start, cache_depth, canonical),
+        HeuristicType::Pos => {
+            let mut heuristic = PosHeuristic::default();
+            load_pos_heuristic(&mut heuristic);
+            search_all(heuristic, heuristic_start, cache_depth, canonical)
+        }
+        HeuristicType::Orr => {
+            let mut heuristic = OrrHeuristic::default();
+            load_orr_heuristic(&mut heuristic);
+            search_all(heuristic, heuristic_start, cache_depth, canonical)
+        }
+        HeuristicType::PosAndOrr => {
+            let mut heuristic = FullHeuristic::default();
+            load_orr_heuristic(&mut heuristic);
+            load_pos_heuristic(&mut heuristic);
+            search_all(heuristic, heuristic_start, cache_depth, canonical)
+        }
+        HeuristicType::Table => search_all(
+            TableHeuristic::build(),
+            heuristic_start,
+            cache_depth,
+            canonical,
+        ),
+    }
+}
 
-    // Leaving this in here in case we switch back ...
-    load_orr_heuristic(&mut heuristic);
-    load_pos_heuristic(&mut heuristic);
+fn search_all<H: Heuristic>(
+    mut heuristic: H,
+    heuristic_start: Instant,
+    cache_depth: usize,
+    canonical: Option<&[bool]>,
+) -> LenBoundReport {
+    let heuristic_time = heuristic_start.elapsed();
 
     let mut short_circuit_cache = SimpleShortCircuitCache::default();
 
     // Note: we know the front/top goal facelets because DLB is fixed
-    // Basically this means we'll precompute everything of length up to 10 (which takes about
-    // half of the allotted time) ...
-    const DEPTH_SIZE: usize = 10;
+    // Basically this means we'll precompute everything of length up to the cache depth (at the
+    // default of 10, that takes about half of the allotted time) ...
     let cache_start = Instant::now();
-    short_circuit_cache.load_with_depth(DEPTH_SIZE, Facelet::Green, Facelet::Yellow);
-
-    println!(
-        "Computed relevant solutions up to depth {} in {:?}",
-        DEPTH_SIZE,
-        cache_start.elapsed()
+    short_circuit_cache.load_with_depth(
+        cache_depth,
+        Cube::make_solved(Facelet::Green, Facelet::Yellow),
+        &MoveSet::all(),
     );
+    let cache_time = cache_start.elapsed();
 
-    println!(
-        "Short circuit cache has {} unique patterns in it",
-        short_circuit_cache.cache_size()
+    eprintln!(
+        "Computed relevant solutions up to depth {} in {:?}",
+        cache_depth, cache_time
     );
 
     // ... then IDA* every possible combination, short-circuiting as soon as we hit something
-    // of accessibility 10 or less. Which is almost immediate.
-    try_combinations(&mut heuristic, &short_circuit_cache)
-
-    // (experimentally, this was the sweet spot between spending your whole time in the cache, and
-    // spending too long per combination)
+    // within the cache depth. Which (at depth 10) is almost immediate.
+    let search_start = Instant::now();
+    let (length_bound, states_solved) =
+        try_combinations(&mut heuristic, &short_circuit_cache, canonical);
+
+    // (experimentally, depth 10 was the sweet spot between spending your whole time in the cache,
+    // and spending too long per combination)
+
+    LenBoundReport {
+        length_bound,
+        cache_depth,
+        cache_size: short_circuit_cache.cache_size(),
+        states_solved,
+        heuristic_time,
+        cache_time,
+        search_time: search_start.elapsed(),
+    }
 }
 
 // TODO: to parallelize we need to refactor to allow the immutable reference, pass on that for now
-fn try_combinations<H: Heuristic, S: ShortCircuitCache>(h: &mut H, s: &S) -> usize {
+/// The longest optimal solution of any state, and how many states it solved to find that; states
+/// which aren't marked canonical (by rank; see [canonical_states]) are skipped
+fn try_combinations<H: Heuristic, S: ShortCircuitCache>(
+    h: &mut H,
+    s: &S,
+    canonical: Option<&[bool]>,
+) -> (usize, usize) {
     let mut my_cube = Cube::make_solved(Facelet::Green, Facelet::White);
 
     let mut all_cubies: VecDeque<[Facelet; 3]> = ALL_CUBIES.clone().into_iter().collect();
@@ -71,13 +156,15 @@ fn try_combinations<H: Heuristic, S: ShortCircuitCache>(h: &mut H, s: &S) -> usi
         remaining_cubelets: &mut VecDeque<[Facelet; 3]>,
         h: &mut H,
         s: &S,
+        canonical: Option<&[bool]>,
+        solved: &mut usize,
         start: &Instant,
     ) -> usize {
         let num_trials = remaining_cubelets.len();
         let next_pos = 8 - num_trials;
 
         if next_pos < 3 {
-            println!(
+            eprintln!(
                 "Walking; next pos is {} (elapsed {:?})",
                 next_pos,
                 start.elapsed()
@@ -92,13 +179,19 @@ fn try_combinations<H: Heuristic, S: ShortCircuitCache>(h: &mut H, s: &S) -> usi
             for orr in 0..3 {
                 put_cubie(cube, next_pos as i32, next_cubelet.clone(), orr);
                 if next_pos == 7 {
-                    if !cube.clone().make_orr_arr_from_dlb().is_solvable() {
+                    if !cube.clone().make_orr_arr_from_dlb().is_solvable()
+                        || canonical.is_some_and(|c| !c[rank_cube(cube)])
+                    {
                         continue;
                     }
-                    let len = optimal_solve_heuristic(cube.clone(), h, s).len();
+                    *solved += 1;
+                    let len = optimal_solve_heuristic(cube.clone(), h, s, &MoveSet::all())
+                        .expect("Every state can be solved")
+                        .len();
                     running_max = running_max.max(len);
                 } else {
-                    let worst = recursive_walk(cube, remaining_cubelets, h, s, start);
+                    let worst =
+                        recursive_walk(cube, remaining_cubelets, h, s, canonical, solved, start);
                     running_max = running_max.max(worst);
                 }
             }
@@ -109,7 +202,18 @@ fn try_combinations<H: Heuristic, S: ShortCircuitCache>(h: &mut H, s: &S) -> usi
         running_max
     }
 
-    recursive_walk(&mut my_cube, &mut all_cubies, h, s, &start_time)
+    let mut solved = 0;
+    let worst = recursive_walk(
+        &mut my_cube,
+        &mut all_cubies,
+        h,
+        s,
+        canonical,
+        &mut solved,
+        &start_time,
+    );
+
+    (worst, solved)
 }
 
 fn load_pos_heuristic<H: Heuristic>(h: &mut H) {
@@ -154,7 +258,7 @@ fn load_pos_heuristic<H: Heuristic>(h: &mut H) {
 
     let elapsed = start.elapsed();
 
-    println!(
+    eprintln!(
         "Computed position solutions (DLB fixed) for {} unique position permutations in {:?}",
         considered, elapsed
     );
@@ -225,7 +329,7 @@ fn load_orr_heuristic<H: Heuristic>(h: &mut H) {
 
     let elapsed = start.elapsed();
 
-    println!(
+    eprintln!(
         "Computed orientation solutions (DLB fixed) for {} unique orientation permutations in {:?}",
         considered, elapsed
     );
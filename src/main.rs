@@ -1,119 +1,1571 @@
+// The cube notation is all acronyms (DLB, OLL, CFL, ...) and reads better in capitals
+#![allow(clippy::upper_case_acronyms)]
+
+use std::path::PathBuf;
 use std::time::Instant;
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 
-use crate::cube::{Cube, Facelet};
-use crate::full_solve::{optimal_solve, HeuristicType};
+use crate::cayley::{CayleyGraph, GraphFormat, NodeLabel};
+use crate::color_scheme::ColorScheme;
+use crate::cube::{Cube, CubeletPos};
+use crate::dist_table::{DistanceTable, Metric, NUM_STATES};
+use crate::distribution::Distribution;
+use crate::full_solve::{optimal_solve_report, HeuristicType};
 use crate::len_bound::compute_len_bound;
-use crate::moves::{flipped, nice_write, CanFullMove, FullMove};
-use crate::scramble::{
-    full_scramble, scramble_cfl, scramble_cfl_oll, scramble_cll, scramble_ofl, scramble_oll,
+use crate::masked::{MaskedCube, MaskedGoal};
+use crate::moves::{
+    flipped, flipped_full, nice_write, CanFullMove, CanMove, FullMove, Move, MoveSet,
+};
+use crate::output::{
+    move_strings, print_json, AntipodeOutput, AntipodesOutput, Format, LengthBoundOutput,
+    PathOutput, PatternOutput, ScrambleOutput, SolveOutput, SubgroupOutput, Timings,
 };
+use crate::pattern::Pattern;
+use crate::render::{render_svg, Mask, View};
+use crate::scramble::{scramble_at_distance, ScrambleKind};
+use crate::subgroup::Subgroup;
 
+mod alg;
+mod analyze;
+mod antipodes;
+mod blind;
+mod cases;
+mod cayley;
+mod color_scheme;
+mod comm;
 mod cube;
+mod dist_table;
+mod distribution;
 mod full_solve;
+mod generate;
+mod heuristic_report;
+mod hint;
 mod len_bound;
+mod masked;
 mod moves;
 mod orr_solve;
+mod output;
+mod path;
+mod pattern;
+mod permutation;
 mod pos_solve;
+mod render;
+mod repl;
 mod scramble;
+mod search;
 mod setup;
+mod subgroup;
+mod symmetry;
+mod verify;
+
+fn solve_input(
+    format: Format,
+    search: SearchArgs,
+    moves: &MoveSet,
+    input: &str,
+    cube: Cube,
+) -> Result<(), i32> {
+    if format == Format::Human {
+        println!("Starting from:\n{}", cube);
+    }
+
+    let state = cube.to_facelet_string();
+    let Some(report) = optimal_solve_report(cube, search.heuristic, search.cache_depth, moves)
+    else {
+        eprintln!("This can't be solved using only {}", moves);
+        return Err(1);
+    };
+
+    match format {
+        Format::Human => {
+            println!(
+                "Precomputed cache of depth {} ({} states) in {:?}",
+                report.cache_depth, report.cache_size, report.cache_time
+            );
+            println!(
+                "Full solution to input in {} moves:\n{}",
+                report.solution.len(),
+                nice_write(&report.solution)
+            );
+            println!("Search took {:?}", report.search_time);
+        }
+        Format::Json => print_json(&SolveOutput {
+            input: input.to_string(),
+            state,
+            solution: move_strings(&report.solution),
+            length: report.solution.len(),
+            cache_size: report.cache_size,
+            timings: Timings::new(report.cache_time, report.search_time),
+        }),
+    }
+
+    Ok(())
+}
+
+fn scramble_by_kind(format: Format, scheme: &ColorScheme, search: SearchArgs, kind: ScrambleKind) {
+    let scramble = kind.scramble(scheme);
+
+    if format == Format::Human {
+        println!("Starting from:\n{}", scramble);
+    }
+
+    let report = optimal_solve_report(
+        scramble.clone(),
+        search.heuristic,
+        search.cache_depth,
+        &MoveSet::all(),
+    )
+    .expect("Every scramble can be solved");
+    let steps = flipped(&report.solution);
+
+    match format {
+        Format::Human => {
+            println!(
+                "Full solution to scramble in {} moves",
+                report.solution.len()
+            );
+            println!("Search took {:?}", report.search_time);
+
+            println!("Scramble given by: {}", nice_write(&steps));
+            println!("Scrambled state: {}", scramble.to_facelet_string());
+        }
+        Format::Json => print_json(&ScrambleOutput {
+            scramble: move_strings(&steps),
+            solution: move_strings(&report.solution),
+            length: report.solution.len(),
+            state: scramble.to_facelet_string(),
+            cache_size: report.cache_size,
+            timings: Timings::new(report.cache_time, report.search_time),
+        }),
+    }
+}
+
+fn scramble_by_distance(
+    format: Format,
+    scheme: &ColorScheme,
+    distance: usize,
+    count: usize,
+) -> Result<(), i32> {
+    let start = Instant::now();
+    let table = DistanceTable::build();
+    let table_time = start.elapsed();
+
+    if format == Format::Human {
+        println!("Built distance table in {:?}", table_time);
+    }
+
+    let scrambles = scramble_at_distance(&table, scheme, distance, count);
+
+    if scrambles.is_empty() {
+        eprintln!(
+            "There are no states at distance {}; the furthest is {}",
+            distance,
+            table.max_distance()
+        );
+        return Err(1);
+    } else if scrambles.len() < count {
+        eprintln!(
+            "There are only {} states at distance {}",
+            scrambles.len(),
+            distance
+        );
+    }
+
+    for scramble in scrambles {
+        let search_start = Instant::now();
+        let solution = table.solve(&scramble);
+        let search_time = search_start.elapsed();
+        let steps = flipped(&solution);
+
+        match format {
+            Format::Human => {
+                println!("Starting from:\n{}", scramble);
+                println!("Scramble given by: {}", nice_write(&steps));
+                println!("Scrambled state: {}", scramble.to_facelet_string());
+            }
+            Format::Json => print_json(&ScrambleOutput {
+                scramble: move_strings(&steps),
+                solution: move_strings(&solution),
+                length: solution.len(),
+                state: scramble.to_facelet_string(),
+                cache_size: NUM_STATES,
+                timings: Timings::new(table_time, search_time),
+            }),
+        }
+    }
+
+    Ok(())
+}
+
+fn length_bound(format: Format, heuristic_type: HeuristicType, cache_depth: usize, symmetry: bool) {
+    let start = Instant::now();
+    let report = compute_len_bound(heuristic_type, cache_depth, symmetry);
+    let elapsed = start.elapsed();
+
+    match format {
+        Format::Human => {
+            println!(
+                "Short circuit cache of depth {} has {} unique patterns in it",
+                report.cache_depth, report.cache_size
+            );
+            println!(
+                "Determined the optimal length bound for the pocket cube to be {}, solving {} states",
+                report.length_bound, report.states_solved
+            );
+            println!("Derivation took {:?}", elapsed);
+        }
+        Format::Json => print_json(&LengthBoundOutput {
+            length_bound: report.length_bound,
+            cache_depth: report.cache_depth,
+            cache_size: report.cache_size,
+            states_solved: report.states_solved,
+            timings: Timings {
+                heuristic_secs: Some(report.heuristic_time.as_secs_f64()),
+                ..Timings::new(report.cache_time, report.search_time)
+            },
+        }),
+    }
+}
+
+fn compare_heuristics(format: Format, sample: Option<usize>) {
+    let start = Instant::now();
+    let table = DistanceTable::build();
+    let report = heuristic_report::heuristic_report(&table, sample);
+
+    if format == Format::Json {
+        print_json(&report);
+        return;
+    }
+
+    if report.sampled {
+        println!(
+            "Compared against the true distance of {} random states",
+            report.states
+        );
+    } else {
+        println!(
+            "Compared against the true distance of every state ({})",
+            report.states
+        );
+    }
+
+    println!("Heuristic  Average gap  Exact");
+    for stats in &report.heuristics {
+        println!(
+            "{:<9}  {:>11.3}  {:>4.1}%",
+            stats.name,
+            stats.average_gap,
+            100.0 * stats.exact
+        );
+    }
+
+    for stats in &report.heuristics {
+        println!();
+        println!(
+            "States by true distance (down) and {} estimate (across):",
+            stats.name
+        );
+        let width = report.states.to_string().len();
+        let estimates: Vec<String> = (0..stats.pairs[0].len())
+            .map(|e| format!("{:>width$}", e, width = width))
+            .collect();
+        println!("    {}", estimates.join(" "));
+        for (distance, row) in stats.pairs.iter().enumerate() {
+            let counts: Vec<String> = row
+                .iter()
+                .map(|c| format!("{:>width$}", c, width = width))
+                .collect();
+            println!("{:>2}  {}", distance, counts.join(" "));
+        }
+    }
+
+    println!();
+    println!("Predicted nodes for an IDA* iteration to each depth:");
+    let names: Vec<String> = report
+        .heuristics
+        .iter()
+        .map(|s| format!("{:>14}", s.name))
+        .collect();
+    println!("Depth  {:>14} {}", "none", names.join(" "));
+    for (depth, brute) in report.brute_force_nodes.iter().enumerate() {
+        let predicted: Vec<String> = report
+            .heuristics
+            .iter()
+            .map(|s| format!("{:>14.0}", s.predicted_nodes[depth]))
+            .collect();
+        println!("{:>5}  {:>14.0} {}", depth, brute, predicted.join(" "));
+    }
+
+    println!("Took {:?}", start.elapsed());
+}
+
+fn distribution_report(format: Format, metric: Metric, csv: bool) {
+    let start = Instant::now();
+    let dist = Distribution::compute(metric);
+
+    if format == Format::Json {
+        print_json(&dist);
+    } else if csv {
+        print!("{}", dist.to_csv());
+    } else {
+        println!(
+            "Distances in the {:?}, counted by states and by classes of symmetric states:",
+            metric
+        );
+        print!("{}", dist.to_table());
+        println!("Took {:?}", start.elapsed());
+    }
+}
+
+fn subgroup_report(format: Format, moves: MoveSet) {
+    let start = Instant::now();
+    let group = Subgroup::explore(moves);
+
+    if format == Format::Json {
+        print_json(&SubgroupOutput {
+            moves: group.moves.to_string(),
+            size: group.size(),
+            diameter: group.diameter(),
+            states: group.histogram().to_vec(),
+        });
+        return;
+    }
+
+    println!(
+        "{} has {} states ({:.4}% of the cube), and everything is within {} moves of solved",
+        group.moves,
+        group.size(),
+        100.0 * group.fraction(),
+        group.diameter()
+    );
+    println!("Distance  States");
+    for (distance, count) in group.histogram().iter().enumerate() {
+        println!("{:>8}  {}", distance, count);
+    }
+    println!("Took {:?}", start.elapsed());
+}
+
+fn export_graph(
+    scheme: &ColorScheme,
+    moves: MoveSet,
+    depth: Option<usize>,
+    format: GraphFormat,
+    label: NodeLabel,
+    output: Option<PathBuf>,
+) -> Result<(), i32> {
+    // without a depth, take the whole subgroup; either way, the subgroup's histogram says how
+    // many states that is before walking out to them
+    let group = Subgroup::explore(moves.clone());
+    let depth = depth.map_or(group.diameter(), |depth| depth.min(group.diameter()));
+    let size: usize = group.histogram()[..=depth].iter().sum();
+
+    if size > cayley::MAX_NODES {
+        eprintln!(
+            "{} has {} states within {} moves of solved, which is too many to draw; give a \
+             smaller --depth",
+            group.moves, size, depth
+        );
+        return Err(1);
+    }
+
+    let graph = CayleyGraph::around(scheme.solved_cube(), moves, depth);
+    let written = graph.write(format, label);
+
+    match output {
+        None => print!("{}", written),
+        Some(path) => {
+            std::fs::write(&path, written).map_err(|e| {
+                eprintln!("Could not write {:?}: {}", path, e);
+                1
+            })?;
+            println!(
+                "Wrote {} states and {} moves to {:?}",
+                graph.nodes.len(),
+                graph.edges.len(),
+                path
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn list_antipodes(format: Format, scheme: &ColorScheme, metric: Metric, all: bool) {
+    let table = DistanceTable::build_in(metric);
+    let distance = table.max_distance();
+    let listed = antipodes::antipodes(&table, !all);
+
+    let states: usize = table.states_at_distance(distance).len();
+    let classes = if all {
+        antipodes::antipodes(&table, true).len()
+    } else {
+        listed.len()
+    };
+
+    let scrambled = |solution: &[Move]| scheme.solved_cube().apply_many(&flipped(solution));
+
+    if format == Format::Json {
+        print_json(&AntipodesOutput {
+            metric,
+            distance,
+            states,
+            classes,
+            antipodes: listed
+                .iter()
+                .map(|a| AntipodeOutput {
+                    state: scrambled(&a.solution).to_facelet_string(),
+                    solution: move_strings(&a.solution),
+                    class_size: a.class_size,
+                })
+                .collect(),
+        });
+        return;
+    }
+
+    println!(
+        "{} states are {} moves from solved in the {:?}, in {} classes of symmetric states",
+        states, distance, metric, classes
+    );
+
+    for antipode in &listed {
+        println!(
+            "{}  {}  ({} symmetric)",
+            scrambled(&antipode.solution).to_facelet_string(),
+            nice_write(&antipode.solution),
+            antipode.class_size
+        );
+    }
+}
+
+fn canonical_input(scheme: &ColorScheme, input: &str, rotations_only: bool) -> Result<(), i32> {
+    let cube = setup::parse_cube(input, scheme).map_err(|e| {
+        eprintln!("Could not parse input {:?}", e);
+        1
+    })?;
+
+    let symmetries = if rotations_only {
+        symmetry::rotations()
+    } else {
+        symmetry::all_symmetries()
+    };
+
+    let canonical = symmetry::canonicalize(&cube, &symmetries);
+    let describe = |sym: &symmetry::Symmetry| match sym.name() {
+        "" => "nothing".to_string(),
+        name => name.to_string(),
+    };
+
+    println!("Starting from:\n{}", cube);
+    println!(
+        "Least symmetric state (rank {}):\n{}",
+        canonical.state, canonical.cube
+    );
+    println!("Facelets: {}", canonical.cube.to_facelet_string());
+    println!("Conjugate by: {}", describe(&canonical.symmetry));
+    println!("Back again by: {}", describe(&canonical.symmetry.inverse()));
+
+    Ok(())
+}
+
+fn transform_alg(scheme: &ColorScheme, alg: &str, rotate: Option<&str>) -> Result<(), i32> {
+    let moves = setup::parse_line(alg).map_err(|e| {
+        eprintln!("Could not parse token {:?}", e);
+        1
+    })?;
+    let extra = match rotate {
+        Some(rotate) => setup::parse_rotations(rotate).map_err(|e| {
+            eprintln!("Could not parse rotation {:?}", e);
+            1
+        })?,
+        None => Vec::new(),
+    };
+
+    let transformed = alg::transforms(&scheme.solved_cube(), &moves, &extra);
+    let width = transformed.iter().map(|t| t.name.len()).max().unwrap_or(0);
+
+    for t in &transformed {
+        let mark = if t.verified {
+            ""
+        } else {
+            "  (does not check out!)"
+        };
+        println!(
+            "{:<width$}  {}{}",
+            format!("{}:", t.name),
+            nice_write(&t.moves),
+            mark,
+            width = width + 1
+        );
+    }
+
+    if transformed.iter().all(|t| t.verified) {
+        Ok(())
+    } else {
+        Err(1)
+    }
+}
+
+fn compare_algs(scheme: &ColorScheme, first: &str, second: &str) -> Result<(), i32> {
+    let parse = |alg| {
+        setup::parse_line(alg).map_err(|e| {
+            eprintln!("Could not parse token {:?}", e);
+            1
+        })
+    };
+    let (a, b) = (parse(first)?, parse(second)?);
+
+    // the first alg with its adjustments, like "U' [R U R'] U2"
+    let adjusted = |pre: &[FullMove], alg: &[FullMove], post: &[FullMove]| {
+        [
+            nice_write(pre),
+            format!("[{}]", nice_write(alg)),
+            nice_write(post),
+        ]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+    };
+
+    match alg::equivalence(&scheme.solved_cube(), &a, &b) {
+        alg::Equivalence::Exact => println!("They do exactly the same thing"),
+        alg::Equivalence::Auf { pre, post } => {
+            println!(
+                "The same up to AUF: {} does the same as the second",
+                adjusted(&pre, &a, &post)
+            );
+        }
+        alg::Equivalence::Rotation {
+            rotation,
+            pre,
+            post,
+        } => {
+            let rotated = moves::rotated_full(&a, &rotation);
+            let rotation: Vec<String> = rotation.iter().map(|r| r.to_string()).collect();
+            println!(
+                "The same up to rotation: held turned by {}, the first is {}",
+                rotation.join(" "),
+                nice_write(&rotated)
+            );
+            if !pre.is_empty() || !post.is_empty() {
+                println!(
+                    "Then with AUF: {} does the same as the second",
+                    adjusted(&pre, &rotated, &post)
+                );
+            }
+        }
+        alg::Equivalence::Recolor {
+            symmetry,
+            pre,
+            post,
+        } => {
+            println!("The same up to recoloring, by the symmetry {}", symmetry);
+            if !pre.is_empty() || !post.is_empty() {
+                println!("With AUF: {}", adjusted(&pre, &a, &post));
+            }
+        }
+        alg::Equivalence::Different => {
+            println!("They do different things");
+            return Err(1);
+        }
+    }
+
+    Ok(())
+}
+
+fn alg_order(scheme: &ColorScheme, alg: &str) -> Result<(), i32> {
+    let moves = setup::parse_line(alg).map_err(|e| {
+        eprintln!("Could not parse token {:?}", e);
+        1
+    })?;
+
+    let solved = scheme.solved_cube();
+    let effect = permutation::CornerPermutation::from_cube(&solved.clone().apply_many_full(&moves));
+
+    println!("Order: {}", alg::order(&solved, &moves));
+    println!("Corners: {}", effect.describe());
+
+    for cycle in effect.cycles() {
+        let corners: Vec<String> = cycle.corners.iter().map(|c| format!("{:?}", c)).collect();
+        let twist = match cycle.twist {
+            0 => String::new(),
+            twist => format!(", twisted by {}", twist),
+        };
+
+        println!("  ({}){}", corners.join(" "), twist);
+    }
+
+    Ok(())
+}
+
+fn find_commutators(
+    cycle: Option<Vec<String>>,
+    twist: Option<Vec<String>>,
+    setup: usize,
+    first: usize,
+    second: usize,
+    count: usize,
+) -> Result<(), i32> {
+    let corners = |names: Vec<String>| -> Result<Vec<CubeletPos>, i32> {
+        names
+            .iter()
+            .map(|name| {
+                blind::parse_corner(name).ok_or_else(|| {
+                    eprintln!("Could not parse corner {:?}", name);
+                    1
+                })
+            })
+            .collect()
+    };
+
+    let effect = match (cycle, twist) {
+        (Some(cycle), None) => {
+            // a repeated --cycle collects all of them
+            let [a, b, c]: [CubeletPos; 3] = corners(cycle)?.try_into().map_err(|_| {
+                eprintln!("Give exactly three corners to --cycle");
+                1
+            })?;
+            comm::Effect::Cycle([a, b, c])
+        }
+        (None, Some(twist)) => {
+            let [a, b]: [CubeletPos; 2] = corners(twist)?.try_into().map_err(|_| {
+                eprintln!("Give exactly two corners to --twist");
+                1
+            })?;
+            comm::Effect::Twist([a, b])
+        }
+        _ => {
+            eprintln!("Give exactly one of --cycle and --twist");
+            return Err(1);
+        }
+    };
+
+    effect.check().map_err(|e| {
+        eprintln!("{}", e);
+        1
+    })?;
+
+    let limits = comm::Limits {
+        setup,
+        first,
+        second,
+    };
+    let found = comm::find_commutators(&effect, limits, count);
+
+    if found.is_empty() {
+        eprintln!("Nothing found within those lengths; try allowing longer parts");
+        return Err(1);
+    }
+
+    for commutator in &found {
+        println!(
+            "{} ({} moves): {}",
+            commutator,
+            commutator.len(),
+            nice_write(&commutator.cancelled())
+        );
+    }
+
+    Ok(())
+}
+
+fn generate_algs(
+    scheme: &ColorScheme,
+    input: &str,
+    case: bool,
+    options: generate::Options,
+    count: usize,
+) -> Result<(), i32> {
+    let start = match setup::parse_line(input) {
+        Ok(moves) if case => {
+            MaskedCube::from_cube(&scheme.solved_cube().apply_many_full(&flipped_full(&moves)))
+        }
+        _ => setup::parse_masked_cube(input, scheme).map_err(|e| {
+            eprintln!("Could not parse input {:?}", e);
+            1
+        })?,
+    };
+
+    println!("Case: {}", start.to_facelet_string());
+
+    let started = Instant::now();
+    let case = generate::Case::from_masked(start);
+    case.check(scheme).map_err(|e| {
+        eprintln!("{}", e);
+        1
+    })?;
+    let found = generate::generate(&case, scheme, &options);
+
+    println!(
+        "Found {} algorithms in {} of up to {} moves, in {:?}",
+        found.len(),
+        options.moves,
+        options.max_length,
+        started.elapsed()
+    );
+
+    for alg in found.iter().take(count) {
+        println!("{:>5.1}  {}", alg.score, alg.write());
+    }
+
+    Ok(())
+}
+
+fn render_input(
+    scheme: &ColorScheme,
+    input: &str,
+    svg: Option<PathBuf>,
+    view: View,
+    mask: Mask,
+    arrows: bool,
+    case: bool,
+) -> Result<(), i32> {
+    let cube = if case {
+        let moves = setup::parse_line(input).map_err(|e| {
+            eprintln!(
+                "A case has to be given by moves; could not parse token {:?}",
+                e
+            );
+            1
+        })?;
+
+        scheme.solved_cube().apply_many_full(&flipped_full(&moves))
+    } else {
+        setup::parse_cube(input, scheme).map_err(|e| {
+            eprintln!("Could not parse input {:?}", e);
+            1
+        })?
+    };
+
+    match svg {
+        None => print!("{}", cube),
+        Some(path) => {
+            std::fs::write(&path, render_svg(&cube, view, mask, arrows)).map_err(|e| {
+                eprintln!("Could not write {:?}: {}", path, e);
+                1
+            })?;
+            println!("Wrote {:?}", path);
+        }
+    }
+
+    Ok(())
+}
+
+fn hint_input(format: Format, scheme: &ColorScheme, input: &str) -> Result<(), i32> {
+    let cube = setup::parse_cube(input, scheme).map_err(|e| {
+        eprintln!("Could not parse input {:?}", e);
+        1
+    })?;
+
+    let table = DistanceTable::build();
+    let distance = table.distance(&cube);
+    let hints = hint::hints(&cube, &table);
+
+    if format == Format::Json {
+        print_json(&hint::HintOutput::new(input, distance, &hints));
+        return Ok(());
+    }
+
+    println!("Starting from:\n{}", cube);
+    println!("Currently {} moves from solved", distance);
+    println!("(* marks moves on an optimal solution)");
+
+    for (grade, heading) in [
+        (hint::Grade::Better, "Better"),
+        (hint::Grade::Same, "Same"),
+        (hint::Grade::Worse, "Worse"),
+    ] {
+        let group: Vec<&hint::MoveHint> = hints.iter().filter(|h| h.grade == grade).collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        println!("{}:", heading);
+        for h in group {
+            let mark = if h.optimal() { "*" } else { " " };
+            println!("  {} {:<3} -> {}", mark, h.m.to_string(), h.distance);
+        }
+    }
+
+    Ok(())
+}
+
+fn path_between(
+    format: Format,
+    scheme: &ColorScheme,
+    search: SearchArgs,
+    moves: &MoveSet,
+    from: &str,
+    to: &str,
+) -> Result<(), i32> {
+    let parse = |input| {
+        setup::parse_cube(input, scheme).map_err(|e| {
+            eprintln!("Could not parse input {:?}", e);
+            1
+        })
+    };
+
+    let (start, goal) = (parse(from)?, parse(to)?);
+
+    let Some(report) =
+        path::optimal_path(&start, &goal, search.heuristic, search.cache_depth, moves)
+    else {
+        eprintln!(
+            "The two states are not made of the same cubies, or {} can't get from one to the other",
+            moves
+        );
+        return Err(1);
+    };
 
-const HEURISTIC_TYPE: HeuristicType = HeuristicType::Orr;
+    match format {
+        Format::Human => {
+            println!("Starting from:\n{}", start);
+            println!("Going to:\n{}", goal);
+            println!(
+                "Shortest path in {} moves:\n{}",
+                report.solution.len(),
+                nice_write(&report.solution)
+            );
+            if start.apply_many(&report.solution) != goal {
+                println!("(this reaches the target with the whole cube turned)");
+            }
+            println!("Search took {:?}", report.search_time);
+        }
+        Format::Json => print_json(&PathOutput {
+            from: from.to_string(),
+            to: to.to_string(),
+            solution: move_strings(&report.solution),
+            length: report.solution.len(),
+            cache_size: report.cache_size,
+            timings: Timings::new(report.cache_time, report.search_time),
+        }),
+    }
 
-fn solve_input(heuristic_type: HeuristicType, input: &[FullMove]) {
-    let cube = Cube::make_solved(Facelet::Green, Facelet::White).apply_many_full(input);
+    Ok(())
+}
+
+fn pattern_search(
+    format: Format,
+    scheme: &ColorScheme,
+    pattern: Option<Pattern>,
+    template: Option<&str>,
+    max_length: usize,
+    limit: usize,
+) -> Result<(), i32> {
+    let goal: Box<dyn Fn(&Cube) -> bool> = match (pattern, template) {
+        (Some(pattern), None) => Box::new(move |cube| pattern.matches(cube)),
+        (None, Some(template)) => {
+            let template = setup::parse_template(template).map_err(|e| {
+                eprintln!("Could not parse template {:?}", e);
+                1
+            })?;
+            Box::new(move |cube| pattern::matches_template(cube, &template))
+        }
+        _ => {
+            eprintln!("Give exactly one of a named pattern or a template");
+            return Err(1);
+        }
+    };
 
     let start = Instant::now();
-    let solution = optimal_solve(cube, heuristic_type);
+    let found = search::find_states(&scheme.solved_cube(), goal, max_length, limit);
     let elapsed = start.elapsed();
 
+    for f in &found {
+        match format {
+            Format::Human => {
+                println!("{} moves: {}", f.moves.len(), nice_write(&f.moves));
+                print!("{}", f.cube);
+            }
+            Format::Json => print_json(&PatternOutput {
+                moves: move_strings(&f.moves),
+                length: f.moves.len(),
+                state: f.cube.to_facelet_string(),
+            }),
+        }
+    }
+
+    if format == Format::Human {
+        println!(
+            "Found {} states within {} moves in {:?}",
+            found.len(),
+            max_length,
+            elapsed
+        );
+    }
+
+    Ok(())
+}
+
+fn masked_solve(
+    scheme: &ColorScheme,
+    input: &str,
+    goal: Option<&str>,
+    max_length: usize,
+) -> Result<(), i32> {
+    let start = setup::parse_masked_cube(input, scheme).map_err(|e| {
+        eprintln!("Could not parse input {:?}", e);
+        1
+    })?;
+
+    let goal = match goal {
+        None => MaskedGoal::Solved,
+        Some(goal) => MaskedGoal::Template(setup::parse_template(goal).map_err(|e| {
+            eprintln!("Could not parse goal {:?}", e);
+            1
+        })?),
+    };
+
+    println!("Starting from: {}", start.to_facelet_string());
+    if !start.is_known() {
+        // enough to get the idea; there can be millions
+        const SHOWN: usize = 10_000;
+        let compatible = start.compatible_cubes(scheme, SHOWN).len();
+        if compatible < SHOWN {
+            println!("There are {} compatible states", compatible);
+        } else {
+            println!("There are at least {} compatible states", SHOWN);
+        }
+    }
+
+    let started = Instant::now();
+    let Some(solution) = masked::masked_solve(&start, &goal, max_length) else {
+        eprintln!(
+            "No sequence of up to {} moves works for every compatible state",
+            max_length
+        );
+        return Err(1);
+    };
+
     println!(
-        "Full solution to input in {} moves:\n{}",
+        "Solution in {} moves:\n{}",
         solution.len(),
         nice_write(&solution)
     );
-    println!("Search took {:?}", elapsed);
-}
-
-#[derive(Subcommand, Copy, Clone, Debug)]
-enum ScrambleKind {
-    /// Performs a full scramble. All permutations are possible.
-    Full,
-    /// Performs an OFL scramble. The bottom layer will be correctly oriented.
-    OFL,
-    /// Performs a CFL scramble. The bottom layer will be completely solved.
-    CFL,
-    /// Performs an OLL scramble. The bottom and top layer will be correctly oriented.
-    OLL,
-    /// Perfectly scrambles a solved cube into a solved state.
-    CLL,
-    /// Leave the bottom layer completely solved, and the top layer oriented correctly
-    // funny naming but it makes clap happy which is all i wanted really
-    #[allow(non_camel_case_types)]
-    CFL_OFL,
+    println!(
+        "Ending at: {}",
+        start.apply_many(&solution).to_facelet_string()
+    );
+    println!("Search took {:?}", started.elapsed());
+
+    Ok(())
+}
+
+fn blind_solve(
+    scheme: &ColorScheme,
+    input: &str,
+    buffer: &str,
+    letters: Option<&str>,
+) -> Result<(), i32> {
+    let cube = setup::parse_cube(input, scheme).map_err(|e| {
+        eprintln!("Could not parse input {:?}", e);
+        1
+    })?;
+
+    let Some(buffer) = blind::parse_corner(buffer) else {
+        eprintln!(
+            "Could not parse buffer {:?}; expected a corner like ULB",
+            buffer
+        );
+        return Err(1);
+    };
+
+    let letters = match letters {
+        Some(letters) => blind::LetterScheme::parse(letters).map_err(|e| {
+            eprintln!("Could not parse letter scheme: {}", e);
+            1
+        })?,
+        None => blind::LetterScheme::speffz(),
+    };
+
+    let op = blind::OldPochmann::new(buffer.clone());
+    let solve = op.solve(&cube, &letters);
+
+    println!("Starting from:\n{}", cube);
+    println!("Buffer: {:?}", buffer);
+    println!(
+        "Swap (buffer to {}): {}",
+        letters.letter(op.helper()),
+        nice_write(op.swap())
+    );
+
+    for cycle in &solve.cycles {
+        let names: Vec<String> = cycle.iter().map(|pos| format!("{:?}", pos)).collect();
+        println!("Cycle: {}", names.join(" -> "));
+    }
+
+    for (pos, orientation) in &solve.twisted {
+        println!(
+            "Twisted in place: {:?} ({:?}), fixed by breaking into it",
+            pos, orientation
+        );
+    }
+
+    println!("Memo: {}", solve.memo());
+
+    for target in &solve.targets {
+        let note = if target.cycle_break {
+            " (cycle break)"
+        } else {
+            ""
+        };
+        println!("  {}{}: {}", target.letter, note, nice_write(&target.moves));
+    }
+
+    println!(
+        "Execution ({} moves): {}",
+        solve.execution.len(),
+        nice_write(&solve.execution)
+    );
+
+    if !blind::verify(&cube, &solve) {
+        eprintln!("The execution does not solve the cube!");
+        return Err(1);
+    }
+
+    println!("Verified: the execution solves the cube");
+    Ok(())
+}
+
+fn verify_solution(scheme: &ColorScheme, scramble: &str, solution: &str) -> Result<(), i32> {
+    let parse = |alg| {
+        setup::parse_line(alg).map_err(|e| {
+            eprintln!("Could not parse token {:?}", e);
+            1
+        })
+    };
+
+    let verdict = verify::verify(scheme.solved_cube(), &parse(scramble)?, &parse(solution)?);
+
+    if verdict.solved {
+        println!("Solved!");
+        return Ok(());
+    }
+
+    println!("Not solved. Remaining state:\n{}", verdict.state);
+    println!("Misplaced corners: {}", verdict.misplaced);
+    println!("Misoriented corners: {}", verdict.misoriented);
+
+    // verify takes no search options, and the table answers exactly without any
+    let remaining = DistanceTable::build().solve(&verdict.state);
+    println!(
+        "Optimal remaining length is {}: {}",
+        remaining.len(),
+        nice_write(&remaining)
+    );
+
+    Err(1)
+}
+
+fn analyze_input(format: Format, scheme: &ColorScheme, input: &str) -> Result<(), i32> {
+    let cube = setup::parse_cube(input, scheme).map_err(|e| {
+        eprintln!("Could not parse input {:?}", e);
+        1
+    })?;
+
+    let table = DistanceTable::build();
+    let analysis = analyze::analyze(input, &cube, &table);
+
+    if format == Format::Json {
+        print_json(&analysis);
+        return Ok(());
+    }
+
+    let faces = |pick: fn(&analyze::FaceReport) -> bool| -> String {
+        let solved: Vec<String> = analysis
+            .faces
+            .iter()
+            .filter(|r| pick(r))
+            .map(|r| r.face.to_string())
+            .collect();
+
+        if solved.is_empty() {
+            "none".to_string()
+        } else {
+            solved.join(" ")
+        }
+    };
+
+    println!("State:\n{}", cube);
+    println!("Facelets: {}", analysis.state);
+    println!(
+        "Positions (relative to DLB): {:?}",
+        cube.clone().make_pos_arr_from_dlb()
+    );
+    println!(
+        "Orientations (relative to DLB): {:?}",
+        cube.clone().make_orr_arr_from_dlb()
+    );
+    println!(
+        "Orientation-only distance: {}",
+        analysis.orientation_distance
+    );
+    println!("Position-only distance: {}", analysis.position_distance);
+    println!(
+        "Optimal distance: {} ({} optimal solutions with DLB fixed)",
+        analysis.distance, analysis.optimal_solutions
+    );
+    println!("Solved faces: {}", faces(|r| r.face_solved));
+    println!("Solved layers: {}", faces(|r| r.layer_solved));
+    println!("Case: {}", analysis.case.unwrap_or("not recognized"));
+
+    Ok(())
+}
+
+// how to run the IDA* search, shared by the commands which do one
+#[derive(Args, Copy, Clone, Debug)]
+struct SearchArgs {
+    /// Which heuristic to guide the search with
+    #[clap(long, value_enum, default_value_t = HeuristicType::Orr, global = true)]
+    heuristic: HeuristicType,
+    /// Precompute every state within this many moves of solved before searching
+    #[clap(long, default_value_t = 5, global = true)]
+    cache_depth: usize,
 }
 
 #[derive(Subcommand, Debug, Clone)]
 enum CubeCommand {
+    /// Finds an optimal solution for a cube, given as a scramble or as its facelets
     Solve {
+        /// Either a sequence of moves to apply to a solved cube, or the 24 facelets of the cube,
+        /// face by face in the order U R F D L B
         permutation: String,
+        /// Only turn these faces, like <R,U> or <U,R2,F2>
+        #[clap(long, default_value = "<R,U,F>")]
+        moves: MoveSet,
+        #[clap(flatten)]
+        search: SearchArgs,
     },
+    /// Makes scrambles, each with an optimal solution
     Scramble {
+        /// What sort of scramble to make; defaults to a full scramble
         #[clap(subcommand)]
-        kind: ScrambleKind,
+        kind: Option<ScrambleKind>,
+        /// Instead of a kind, pick states uniformly among those whose optimal solution is exactly
+        /// this many moves
+        #[clap(long)]
+        distance: Option<usize>,
+        /// How many scrambles to make
+        #[clap(long, default_value_t = 1, global = true)]
+        count: usize,
+        #[clap(flatten)]
+        search: SearchArgs,
+    },
+    /// Solves every state to find the longest optimal solution of any of them
+    LengthBound {
+        /// Which heuristic to guide the search with
+        #[clap(long, value_enum, default_value_t = HeuristicType::PosAndOrr)]
+        heuristic: HeuristicType,
+        /// Precompute every state within this many moves of solved before searching
+        #[clap(long, default_value_t = 10)]
+        cache_depth: usize,
+        /// Only solve one state from each class of symmetric states
+        #[clap(long)]
+        symmetry: bool,
+    },
+    /// Compares what the IDA* heuristics guess against the true distance, and how many nodes a
+    /// search with each should visit
+    HeuristicReport {
+        /// Only look at this many random states, rather than all of them
+        #[clap(long)]
+        sample: Option<usize>,
+    },
+    /// Counts the states at each distance from solved, for the whole cube and for the position
+    /// and orientation subproblems, both raw and up to symmetry
+    Distribution {
+        /// How to count moves
+        #[clap(long, value_enum, default_value_t = Metric::HTM)]
+        metric: Metric,
+        /// Print the table as CSV instead
+        #[clap(long)]
+        csv: bool,
+    },
+    /// Walks every state reachable turning only some of the faces, and counts them by distance
+    Subgroup {
+        /// The faces to turn, like <R,U>, <U,R2,F2> or <R,U2>
+        moves: MoveSet,
+    },
+    /// Writes out the graph of states and the moves between them, for a small subgroup or the
+    /// states near solved
+    Graph {
+        /// The faces to turn, like <R2,U2,F2> or <R,U2>
+        moves: MoveSet,
+        /// Only the states within this many moves of solved; otherwise the whole subgroup
+        #[clap(long)]
+        depth: Option<usize>,
+        #[clap(long, value_enum, default_value_t = GraphFormat::Dot)]
+        graph_format: GraphFormat,
+        /// What to call each state
+        #[clap(long, value_enum, default_value_t = NodeLabel::Facelets)]
+        label: NodeLabel,
+        /// Write the graph to this file instead of printing it
+        #[clap(long)]
+        output: Option<PathBuf>,
+    },
+    /// Lists the states furthest from solved, with an optimal solution for each
+    Antipodes {
+        /// How to count moves
+        #[clap(long, value_enum, default_value_t = Metric::HTM)]
+        metric: Metric,
+        /// List every state, instead of one from each class of symmetric states
+        #[clap(long)]
+        all: bool,
+    },
+    /// Finds the least state symmetric to this one, and the symmetry which gets there
+    Canonical {
+        /// Either a sequence of moves to apply to a solved cube, or the 24 facelets of the cube
+        input: String,
+        /// Only turn the cube around; don't look at it in a mirror
+        #[clap(long)]
+        rotations_only: bool,
+    },
+    /// Works with algorithms as sequences of moves
+    Alg {
+        #[clap(subcommand)]
+        command: AlgCommand,
+    },
+    /// Prints everything we know about a state
+    Analyze {
+        /// Either a sequence of moves to apply to a solved cube, or the 24 facelets of the cube
+        input: String,
+    },
+    /// Finds the shortest sequence of moves from one state to another
+    Path {
+        /// The starting state, as moves from solved or 24 facelets
+        from: String,
+        /// The target state, as moves from solved or 24 facelets
+        to: String,
+        /// Only turn these faces, like <R,U> or <U,R2,F2>
+        #[clap(long, default_value = "<R,U,F>")]
+        moves: MoveSet,
+        #[clap(flatten)]
+        search: SearchArgs,
+    },
+    /// Searches for short sequences which make a sticker pattern, shortest first
+    Pattern {
+        /// A named pattern to look for
+        #[clap(value_enum)]
+        pattern: Option<Pattern>,
+        /// Instead of a named pattern, 24 facelets to match, where ? or . matches anything
+        #[clap(long)]
+        template: Option<String>,
+        /// Only look this many moves out from solved
+        #[clap(long, default_value_t = 8)]
+        max_length: usize,
+        /// Stop after finding this many states
+        #[clap(long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Solves a cube where some facelets are unknown, so the solution works whatever they are
+    MaskedSolve {
+        /// Either a sequence of moves to apply to a solved cube, or 24 facelets, face by face in
+        /// the order U R F D L B, where ? or X is unknown
+        input: String,
+        /// Instead of solving, reach this template of 24 facelets, where ?, . or X can be anything
+        #[clap(long)]
+        goal: Option<String>,
+        /// Only look this many moves out
+        #[clap(long, default_value_t = 11)]
+        max_length: usize,
+    },
+    /// Works out the Old Pochmann memo and execution for solving the corners blindfolded
+    Blind {
+        /// Either a sequence of moves to apply to a solved cube, or the 24 facelets of the cube
+        input: String,
+        /// The buffer corner, like ULB
+        #[clap(long, default_value = "ULB")]
+        buffer: String,
+        /// The letter for every facelet, in Speffz order (U, L, F, R, B, D faces, each clockwise
+        /// from the top left); the default is Speffz itself
+        #[clap(long)]
+        letters: Option<String>,
+    },
+    /// Lists every algorithm up to some length which solves a case, easiest to do first
+    Generate {
+        /// Either a sequence of moves to apply to a solved cube, or 24 facelets, face by face in
+        /// the order U R F D L B, where ? or X doesn't matter
+        input: String,
+        /// Treat the moves as an algorithm which solves the case (rather than one which makes it)
+        #[clap(long)]
+        case: bool,
+        /// Which moves to use, like <R,U> or <R2,U,F2>
+        #[clap(long, default_value = "<R,U,F>")]
+        moves: MoveSet,
+        /// The longest algorithm to look for, not counting the U turns before and after
+        #[clap(long, default_value_t = 9)]
+        max_length: usize,
+        /// Allow a U turn before and after the algorithm
+        #[clap(long)]
+        auf: bool,
+        /// How many algorithms to list
+        #[clap(long, default_value_t = 20)]
+        count: usize,
+    },
+    /// Lists what every next move does to the distance from solved
+    Hint {
+        /// Either a sequence of moves to apply to a solved cube, or the 24 facelets of the cube
+        input: String,
+    },
+    /// Checks that a solution really solves a scramble
+    Verify {
+        /// The moves which scramble a solved cube
+        #[clap(long)]
+        scramble: String,
+        /// The moves which are supposed to solve it again
+        #[clap(long)]
+        solution: String,
+    },
+    /// Starts an interactive session around a single cube state
+    Repl,
+    /// Draws a cube state, in the terminal or as an SVG
+    Render {
+        /// Either a sequence of moves to apply to a solved cube, or the 24 facelets of the cube
+        input: String,
+        /// Write an SVG diagram to this file, instead of drawing in the terminal
+        #[clap(long)]
+        svg: Option<PathBuf>,
+        /// Which picture to draw in the SVG
+        #[clap(long, value_enum, default_value_t = View::Net)]
+        view: View,
+        /// Which facelets to gray out in the SVG
+        #[clap(long, value_enum, default_value_t = Mask::None)]
+        mask: Mask,
+        /// Draw arrows in the SVG showing where the U layer cubelets need to go
+        #[clap(long)]
+        arrows: bool,
+        /// Treat the moves as an algorithm which solves the state (rather than one which makes it)
+        #[clap(long)]
+        case: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum AlgCommand {
+    /// Prints the inverse, the mirrors, and the alg for the cube held other ways round, checking
+    /// each one on a cube
+    Transform {
+        /// The algorithm, as a sequence of moves
+        alg: String,
+        /// Also print the alg for the cube turned by these whole cube rotations, like "x y'"
+        #[clap(long)]
+        rotate: Option<String>,
+    },
+    /// Checks whether two algorithms do the same thing, exactly, up to U turns before and after,
+    /// up to holding the cube another way round, or up to repainting the colors
+    Equiv {
+        /// The first algorithm, as a sequence of moves
+        first: String,
+        /// The second algorithm, as a sequence of moves
+        second: String,
+    },
+    /// Works out how many times the algorithm has to be repeated to get back to solved, and the
+    /// cycles it moves the corners round
+    Order {
+        /// The algorithm, as a sequence of moves
+        alg: String,
+    },
+    /// Finds the shortest commutators [A, B] and conjugates [S: [A, B]] of R, U and F moves which
+    /// cycle three corners or twist two, leaving everything else alone
+    Comm {
+        /// Cycle these three corners, like URF ULF ULB: each goes to the next, keeping its U or D
+        /// facelet on U or D
+        #[clap(long, num_args = 3, value_names = ["FROM", "TO", "THEN"])]
+        cycle: Option<Vec<String>>,
+        /// Twist the first of these two corners clockwise, and the second anticlockwise
+        #[clap(long, num_args = 2, value_names = ["CLOCKWISE", "ANTICLOCKWISE"])]
+        twist: Option<Vec<String>>,
+        /// The longest setup S to try; 0 for only plain commutators
+        #[clap(long, default_value_t = 2)]
+        setup: usize,
+        /// The longest first part A to try
+        #[clap(long, default_value_t = 5)]
+        first: usize,
+        /// The longest second part B to try
+        #[clap(long, default_value_t = 2)]
+        second: usize,
+        /// How many to list
+        #[clap(long, default_value_t = 10)]
+        count: usize,
     },
-    LengthBound,
+}
+
+impl CubeCommand {
+    /// Whether the command can print its results with --format json
+    fn prints_json(&self) -> bool {
+        matches!(
+            self,
+            CubeCommand::Solve { .. }
+                | CubeCommand::Scramble { .. }
+                | CubeCommand::LengthBound { .. }
+                | CubeCommand::HeuristicReport { .. }
+                | CubeCommand::Distribution { .. }
+                | CubeCommand::Subgroup { .. }
+                | CubeCommand::Antipodes { .. }
+                | CubeCommand::Analyze { .. }
+                | CubeCommand::Path { .. }
+                | CubeCommand::Pattern { .. }
+                | CubeCommand::Hint { .. }
+        )
+    }
 }
 
 #[derive(Parser, Debug)]
 struct Arguments {
     #[clap(subcommand)]
     cmd: CubeCommand,
+    /// The color scheme of the cube: western, japanese, or the colors of the U, R, F, D, L and B
+    /// faces (so western is WRGYOB)
+    #[clap(long, global = true, default_value = "western")]
+    scheme: ColorScheme,
+    /// How to print results of solve, scramble, length-bound, heuristic-report, path, pattern,
+    /// analyze, hint, distribution, subgroup and antipodes: human for prose, or json for one JSON
+    /// object per result (the other commands only print prose)
+    #[clap(long, global = true, value_enum, default_value_t = Format::Human)]
+    format: Format,
 }
 
 fn main() -> Result<(), i32> {
     let args = Arguments::parse();
 
+    if args.format == Format::Json && !args.cmd.prints_json() {
+        eprintln!("This command only prints prose, so it can't be used with --format json");
+        return Err(1);
+    }
+
     match args.cmd {
-        CubeCommand::Solve { permutation } => {
-            let parsed = setup::parse_line(&permutation).map_err(|e| {
-                println!("Could not parse token {:?}", e);
+        CubeCommand::Solve {
+            permutation,
+            moves,
+            search,
+        } => {
+            let cube = setup::parse_cube(&permutation, &args.scheme).map_err(|e| {
+                eprintln!("Could not parse input {:?}", e);
                 1
             })?;
 
-            solve_input(HEURISTIC_TYPE, &parsed);
+            solve_input(args.format, search, &moves, &permutation, cube)?;
         }
-        CubeCommand::LengthBound => {
-            let start = Instant::now();
-            let len_bound = compute_len_bound();
-            let elapsed = start.elapsed();
-            println!(
-                "Determined the optimal length bound for the pocket cube to be {}",
-                len_bound
-            );
-            println!("Derivation took {:?}", elapsed);
-        }
-        CubeCommand::Scramble { kind } => {
-            let scramble = match kind {
-                ScrambleKind::Full => full_scramble(),
-                ScrambleKind::OFL => scramble_ofl(),
-                ScrambleKind::CFL => scramble_cfl(),
-                ScrambleKind::OLL => scramble_oll(),
-                ScrambleKind::CLL => scramble_cll(),
-                ScrambleKind::CFL_OFL => scramble_cfl_oll(),
+        CubeCommand::Render {
+            input,
+            svg,
+            view,
+            mask,
+            arrows,
+            case,
+        } => render_input(&args.scheme, &input, svg, view, mask, arrows, case)?,
+        CubeCommand::Alg { command } => match command {
+            AlgCommand::Transform { alg, rotate } => {
+                transform_alg(&args.scheme, &alg, rotate.as_deref())?
+            }
+            AlgCommand::Equiv { first, second } => compare_algs(&args.scheme, &first, &second)?,
+            AlgCommand::Order { alg } => alg_order(&args.scheme, &alg)?,
+            AlgCommand::Comm {
+                cycle,
+                twist,
+                setup,
+                first,
+                second,
+                count,
+            } => find_commutators(cycle, twist, setup, first, second, count)?,
+        },
+        CubeCommand::Analyze { input } => analyze_input(args.format, &args.scheme, &input)?,
+        CubeCommand::Path {
+            from,
+            to,
+            moves,
+            search,
+        } => path_between(args.format, &args.scheme, search, &moves, &from, &to)?,
+        CubeCommand::Pattern {
+            pattern,
+            template,
+            max_length,
+            limit,
+        } => pattern_search(
+            args.format,
+            &args.scheme,
+            pattern,
+            template.as_deref(),
+            max_length,
+            limit,
+        )?,
+        CubeCommand::MaskedSolve {
+            input,
+            goal,
+            max_length,
+        } => masked_solve(&args.scheme, &input, goal.as_deref(), max_length)?,
+        CubeCommand::Blind {
+            input,
+            buffer,
+            letters,
+        } => blind_solve(&args.scheme, &input, &buffer, letters.as_deref())?,
+        CubeCommand::Generate {
+            input,
+            case,
+            moves,
+            max_length,
+            auf,
+            count,
+        } => {
+            let options = generate::Options {
+                moves,
+                max_length,
+                pre_auf: auf,
+                post_auf: auf,
             };
-
-            let start = Instant::now();
-            let solution = optimal_solve(scramble, HeuristicType::Orr);
-            let elapsed = start.elapsed();
-
-            println!("Full solution to scramble in {} moves", solution.len());
-            println!("Search took {:?}", elapsed);
-
-            let steps = flipped(&solution);
-            println!("Scramble given by: {}", nice_write(&steps));
+            generate_algs(&args.scheme, &input, case, options, count)?
+        }
+        CubeCommand::Hint { input } => hint_input(args.format, &args.scheme, &input)?,
+        CubeCommand::Verify { scramble, solution } => {
+            verify_solution(&args.scheme, &scramble, &solution)?
+        }
+        CubeCommand::Repl => repl::run(args.scheme),
+        CubeCommand::Canonical {
+            input,
+            rotations_only,
+        } => canonical_input(&args.scheme, &input, rotations_only)?,
+        CubeCommand::Antipodes { metric, all } => {
+            list_antipodes(args.format, &args.scheme, metric, all)
         }
+        CubeCommand::HeuristicReport { sample } => compare_heuristics(args.format, sample),
+        CubeCommand::Distribution { metric, csv } => distribution_report(args.format, metric, csv),
+        CubeCommand::Subgroup { moves } => subgroup_report(args.format, moves),
+        CubeCommand::Graph {
+            moves,
+            depth,
+            graph_format,
+            label,
+            output,
+        } => export_graph(&args.scheme, moves, depth, graph_format, label, output)?,
+        CubeCommand::LengthBound {
+            heuristic,
+            cache_depth,
+            symmetry,
+        } => length_bound(args.format, heuristic, cache_depth, symmetry),
+        CubeCommand::Scramble {
+            kind,
+            distance,
+            count,
+            search,
+        } => match (kind, distance) {
+            (Some(_), Some(_)) => {
+                eprintln!("Cannot scramble by kind and by distance at the same time");
+                return Err(1);
+            }
+            (None, Some(distance)) => {
+                scramble_by_distance(args.format, &args.scheme, distance, count)?
+            }
+            (kind, None) => {
+                for _ in 0..count {
+                    scramble_by_kind(
+                        args.format,
+                        &args.scheme,
+                        search,
+                        kind.unwrap_or(ScrambleKind::Full),
+                    );
+                }
+            }
+        },
     }
 
     Ok(())
@@ -0,0 +1,360 @@
+//! Cubes where some facelets are unknown, either because you can't see them or because you don't
+//! care about them (say, when all you want is the first layer).
+//!
+//! A sequence of moves "solves" a masked cube when it solves every real cube compatible with it,
+//! and "reaches" a masked goal when every facelet the goal asks for ends up the right color.
+
+use std::collections::HashSet;
+
+use crate::color_scheme::ColorScheme;
+use crate::cube::{Cube, Facelet, ALL_FACELETS, CORNER_FACELETS};
+use crate::moves::{CanFullMove, CanMove, FullMove, Move, ALL_MOVES};
+use crate::scramble::put_cubie;
+
+/// A cube where each facelet is either known, or unknown (None), in the order of
+/// [Cube::to_facelets]
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct MaskedCube {
+    pub facelets: [Option<Facelet>; 24],
+}
+
+/// For each facelet of [Cube::to_facelets], where it was before doing the move
+pub fn facelet_sources(m: Move) -> [usize; 24] {
+    sources_of(|cube| cube.apply(m))
+}
+
+/// The same as [facelet_sources], for any sequence of moves (not just R, U and F)
+pub fn facelet_sources_full(moves: &[FullMove]) -> [usize; 24] {
+    sources_of(|cube| cube.apply_many_full(moves))
+}
+
+/// Moves only shuffle facelets around, so we can find out where each one came from by spelling out
+/// every facelet's index in colors (two base six digits) and seeing where the digits end up
+fn sources_of(shuffle: impl Fn(Cube) -> Cube) -> [usize; 24] {
+    let digit_cube = |digit: fn(usize) -> usize| {
+        Cube::from_facelets(std::array::from_fn(|i| ALL_FACELETS[digit(i)].clone()))
+    };
+
+    let low = shuffle(digit_cube(|i| i % 6)).to_facelets();
+    let high = shuffle(digit_cube(|i| i / 6)).to_facelets();
+
+    let digit_of = |f: &Facelet| ALL_FACELETS.iter().position(|c| c == f).unwrap();
+
+    std::array::from_fn(|i| digit_of(&low[i]) + 6 * digit_of(&high[i]))
+}
+
+impl MaskedCube {
+    pub fn from_cube(cube: &Cube) -> Self {
+        MaskedCube {
+            facelets: cube.to_facelets().map(Some),
+        }
+    }
+
+    /// The facelets as letters, with `?` for unknown ones; [crate::setup::parse_masked] reads this
+    /// back in
+    pub fn to_facelet_string(&self) -> String {
+        self.facelets
+            .iter()
+            .map(|f| f.as_ref().map_or('?', Facelet::letter))
+            .collect()
+    }
+
+    pub fn is_known(&self) -> bool {
+        self.facelets.iter().all(Option::is_some)
+    }
+
+    /// Every real cube which agrees with the known facelets, up to `limit` of them
+    pub fn compatible_cubes(&self, scheme: &ColorScheme, limit: usize) -> Vec<Cube> {
+        let mut out = Vec::new();
+        let mut working = scheme.solved_cube();
+        let mut used = [false; 8];
+
+        self.walk_compatible(scheme, &mut working, 0, &mut used, &mut |cube| {
+            out.push(cube.clone());
+            out.len() < limit
+        });
+
+        out
+    }
+
+    /// Fills in every unknown facelet which is the same in all the compatible cubes (for instance
+    /// the third facelet of a corner when the other two are known, or the twist of the last
+    /// corner). None if there aren't any compatible cubes at all.
+    pub fn completed(&self, scheme: &ColorScheme) -> Option<MaskedCube> {
+        // past this many compatible cubes, give up on anything which is still undecided; this only
+        // ever leaves facelets unknown, it never guesses
+        const LIMIT: usize = 1_000_000;
+
+        let mut seen: [Vec<Facelet>; 24] = std::array::from_fn(|_| Vec::new());
+        let mut count = 0;
+        let mut working = scheme.solved_cube();
+        let mut used = [false; 8];
+
+        self.walk_compatible(scheme, &mut working, 0, &mut used, &mut |cube| {
+            count += 1;
+
+            for (colors, facelet) in seen.iter_mut().zip(cube.to_facelets()) {
+                if colors.len() < 2 && !colors.contains(&facelet) {
+                    colors.push(facelet);
+                }
+            }
+
+            // once every unknown facelet could go two ways, nothing else can be filled in
+            let undecided = self
+                .facelets
+                .iter()
+                .zip(seen.iter())
+                .any(|(known, colors)| known.is_none() && colors.len() < 2);
+
+            undecided && count < LIMIT
+        });
+
+        if count == 0 {
+            return None;
+        }
+
+        let exhausted = count < LIMIT;
+
+        Some(MaskedCube {
+            facelets: std::array::from_fn(|i| match (&self.facelets[i], seen[i].as_slice()) {
+                (Some(known), _) => Some(known.clone()),
+                (None, [only]) if exhausted => Some(only.clone()),
+                (None, _) => None,
+            }),
+        })
+    }
+
+    /// Backtracks over which cubie (and twist) goes in each corner, calling `visit` on each
+    /// compatible cube until it returns false. Returns false if it was told to stop.
+    fn walk_compatible(
+        &self,
+        scheme: &ColorScheme,
+        working: &mut Cube,
+        corner: usize,
+        used: &mut [bool; 8],
+        visit: &mut dyn FnMut(&Cube) -> bool,
+    ) -> bool {
+        if corner == 8 {
+            if !working.clone().make_orr_arr_from_dlb().is_solvable() {
+                return true;
+            }
+            return visit(working);
+        }
+
+        let cubies = scheme.corners();
+
+        for cubie in 0..8 {
+            if used[cubie] {
+                continue;
+            }
+
+            for twist in 0..3 {
+                put_cubie(working, corner as i32, cubies[cubie].clone(), twist);
+
+                if !self.corner_agrees(working, corner) {
+                    continue;
+                }
+
+                used[cubie] = true;
+                let keep_going = self.walk_compatible(scheme, working, corner + 1, used, visit);
+                used[cubie] = false;
+
+                if !keep_going {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    fn corner_agrees(&self, cube: &Cube, corner: usize) -> bool {
+        let placed = cube.to_facelets();
+
+        CORNER_FACELETS[corner].iter().all(|&i| {
+            self.facelets[i]
+                .as_ref()
+                .is_none_or(|known| *known == placed[i])
+        })
+    }
+}
+
+impl CanMove for MaskedCube {
+    fn apply(self, m: Move) -> Self {
+        let sources = facelet_sources(m);
+
+        MaskedCube {
+            facelets: sources.map(|source| self.facelets[source].clone()),
+        }
+    }
+}
+
+/// What a masked solve is aiming for
+pub enum MaskedGoal {
+    /// Every face a single color, in every compatible cube
+    Solved,
+    /// Every facelet the template gives is that color, in every compatible cube
+    Template([Option<Facelet>; 24]),
+}
+
+impl MaskedGoal {
+    fn reached(&self, cube: &MaskedCube) -> bool {
+        match self {
+            MaskedGoal::Solved => cube
+                .facelets
+                .chunks(4)
+                .all(|face| face[0].is_some() && face.iter().all(|f| *f == face[0])),
+            MaskedGoal::Template(template) => template
+                .iter()
+                .zip(cube.facelets.iter())
+                .all(|(want, have)| want.is_none() || want == have),
+        }
+    }
+}
+
+/// The shortest sequence (with DLB fixed) which takes every cube compatible with `start` to the
+/// goal, looking at most `max_length` moves out. The start should already be
+/// [MaskedCube::completed], or it may look like less is known about it than really is.
+///
+/// Every sequence acts on the facelets the same way whatever the colors are, so the search is over
+/// where each facelet has been moved to; there are only as many of those as there are states.
+pub fn masked_solve(start: &MaskedCube, goal: &MaskedGoal, max_length: usize) -> Option<Vec<Move>> {
+    let move_sources: Vec<[usize; 24]> = ALL_MOVES.iter().map(|&m| facelet_sources(m)).collect();
+
+    // sources[i] is where the facelet now at i started out
+    let identity: [u8; 24] = std::array::from_fn(|i| i as u8);
+    let mut seen: HashSet<[u8; 24]> = HashSet::new();
+    seen.insert(identity);
+
+    let mut frontier = vec![(identity, Vec::new())];
+
+    for length in 0..=max_length {
+        let mut next_frontier = Vec::new();
+
+        for (sources, moves) in frontier {
+            let here = MaskedCube {
+                facelets: sources.map(|s| start.facelets[s as usize].clone()),
+            };
+
+            if goal.reached(&here) {
+                return Some(moves);
+            }
+
+            if length == max_length {
+                continue;
+            }
+
+            for (move_ind, m) in ALL_MOVES.iter().enumerate() {
+                if moves.last().map(|last: &Move| last.dir) == Some(m.dir) {
+                    continue;
+                }
+
+                let next: [u8; 24] = move_sources[move_ind].map(|s| sources[s]);
+                if seen.insert(next) {
+                    let mut next_moves = moves.clone();
+                    next_moves.push(*m);
+                    next_frontier.push((next, next_moves));
+                }
+            }
+        }
+
+        frontier = next_frontier;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod masked_tests {
+    use crate::moves::nice_write;
+    use crate::scramble::full_scramble;
+    use crate::setup::parse_line;
+
+    use super::*;
+
+    #[test]
+    fn moves_match_cube_moves() {
+        let cube = full_scramble(&ColorScheme::western());
+
+        for m in ALL_MOVES {
+            assert_eq!(
+                MaskedCube::from_cube(&cube).apply(m),
+                MaskedCube::from_cube(&cube.clone().apply(m))
+            );
+        }
+
+        // and the corner facelet table agrees with Cube::corners
+        let facelets = cube.to_facelets();
+        for (corner, indices) in cube.corners().iter().zip(CORNER_FACELETS) {
+            assert_eq!(*corner, indices.map(|i| facelets[i].clone()));
+        }
+    }
+
+    #[test]
+    fn completing_hidden_corners() {
+        let scheme = ColorScheme::western();
+        let cube = scheme
+            .solved_cube()
+            .apply_many_full(&parse_line("R U F'").unwrap());
+
+        // hide one whole corner, and one facelet of another; both are forced
+        let mut masked = MaskedCube::from_cube(&cube);
+        for i in CORNER_FACELETS[6] {
+            masked.facelets[i] = None;
+        }
+        masked.facelets[CORNER_FACELETS[2][1]] = None;
+
+        assert_eq!(
+            masked.completed(&scheme),
+            Some(MaskedCube::from_cube(&cube))
+        );
+
+        // hide two corners, and they could go either way around
+        masked.facelets[CORNER_FACELETS[7][0]] = None;
+        masked.facelets[CORNER_FACELETS[7][1]] = None;
+        masked.facelets[CORNER_FACELETS[7][2]] = None;
+        let completed = masked.completed(&scheme).unwrap();
+        assert!(!completed.is_known());
+        assert_eq!(masked.compatible_cubes(&scheme, 100).len(), 6);
+
+        // a contradiction has nothing compatible
+        let mut bad = MaskedCube::from_cube(&cube);
+        let [first, second, _] = CORNER_FACELETS[0];
+        bad.facelets[second] = bad.facelets[first].clone();
+        assert_eq!(bad.completed(&scheme), None);
+    }
+
+    #[test]
+    fn solving_masked_cubes() {
+        let scheme = ColorScheme::western();
+        let cube = scheme
+            .solved_cube()
+            .apply_many_full(&parse_line("R U F'").unwrap());
+
+        // fully known is just a normal solve
+        let known = MaskedCube::from_cube(&cube);
+        let solution = masked_solve(&known, &MaskedGoal::Solved, 11).unwrap();
+        assert_eq!(nice_write(&solution), "F U' R'");
+
+        // when only the D face matters, it can take less than solving
+        let cube = scheme
+            .solved_cube()
+            .apply_many_full(&parse_line("U R2 U'").unwrap());
+        let goal = MaskedGoal::Template(
+            crate::setup::parse_template("???? ???? ???? YYYY ???? ????").unwrap(),
+        );
+        let solution = masked_solve(&MaskedCube::from_cube(&cube), &goal, 11).unwrap();
+        assert_eq!(solution.len(), 2);
+        assert!(cube.apply_many(&solution).d.solved());
+
+        // with the whole U layer hidden, there's no telling how to solve it
+        let mut hidden = known.clone();
+        for corner in &CORNER_FACELETS[4..8] {
+            for &i in corner {
+                hidden.facelets[i] = None;
+            }
+        }
+        let hidden = hidden.completed(&scheme).unwrap();
+        assert_eq!(masked_solve(&hidden, &MaskedGoal::Solved, 4), None);
+    }
+}
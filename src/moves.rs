@@ -1,4 +1,5 @@
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 use crate::cube::{Cube, CubeletOrientationArrangement, CubeletPositionArrangement};
 
@@ -24,6 +25,26 @@ impl Amt {
             Amt::Rev => Amt::One,
         }
     }
+
+    /// How many clockwise quarter turns this is
+    pub fn quarter_turns(self) -> usize {
+        match self {
+            Amt::One => 1,
+            Amt::Two => 2,
+            Amt::Rev => 3,
+        }
+    }
+
+    /// The amount for this many clockwise quarter turns, or None if they come back around to
+    /// doing nothing
+    pub fn from_quarter_turns(turns: usize) -> Option<Self> {
+        match turns % 4 {
+            1 => Some(Amt::One),
+            2 => Some(Amt::Two),
+            3 => Some(Amt::Rev),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -41,6 +62,149 @@ impl Move {
     }
 }
 
+/// Every move (with DLB fixed), in a fixed order
+pub const ALL_MOVES: [Move; 9] = [
+    Move {
+        dir: Dir::R,
+        amt: Amt::One,
+    },
+    Move {
+        dir: Dir::R,
+        amt: Amt::Two,
+    },
+    Move {
+        dir: Dir::R,
+        amt: Amt::Rev,
+    },
+    Move {
+        dir: Dir::U,
+        amt: Amt::One,
+    },
+    Move {
+        dir: Dir::U,
+        amt: Amt::Two,
+    },
+    Move {
+        dir: Dir::U,
+        amt: Amt::Rev,
+    },
+    Move {
+        dir: Dir::F,
+        amt: Amt::One,
+    },
+    Move {
+        dir: Dir::F,
+        amt: Amt::Two,
+    },
+    Move {
+        dir: Dir::F,
+        amt: Amt::Rev,
+    },
+];
+
+/// A set of generators to search with, like <R,U> or <R2,U,F2>: a face on its own allows every
+/// turn of it, and with a 2 only the half turn. Either way the set has the inverse of everything
+/// in it, so distances in it go both ways.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct MoveSet {
+    /// Indices into [ALL_MOVES]
+    indices: Vec<usize>,
+}
+
+impl MoveSet {
+    /// Every turn of R, U and F
+    pub fn all() -> Self {
+        MoveSet {
+            indices: (0..ALL_MOVES.len()).collect(),
+        }
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.indices.len() == ALL_MOVES.len()
+    }
+
+    /// The indices (into [ALL_MOVES]) of the moves in the set
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    pub fn moves(&self) -> impl '_ + Iterator<Item = Move> {
+        self.indices.iter().map(|&i| ALL_MOVES[i])
+    }
+}
+
+impl FromStr for MoveSet {
+    type Err = String;
+
+    /// Accepts generators separated by commas or spaces, optionally in angle brackets, like
+    /// "<R,U>", "R U2" or "<R2, U, F2>"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let inner = s.trim().trim_start_matches('<').trim_end_matches('>');
+        let mut indices = Vec::new();
+
+        for generator in inner.split([',', ' ']).filter(|g| !g.is_empty()) {
+            let (dir, amt) = generator.split_at(1.min(generator.len()));
+
+            let dir = match dir {
+                "R" => Dir::R,
+                "U" => Dir::U,
+                "F" => Dir::F,
+                _ => {
+                    return Err(format!(
+                        "Unknown generator {:?}; only R, U and F keep DLB where it is",
+                        generator
+                    ))
+                }
+            };
+
+            let amts: &[Amt] = match amt {
+                "" => &[Amt::One, Amt::Two, Amt::Rev],
+                "2" => &[Amt::Two],
+                _ => return Err(format!("Unknown generator {:?}", generator)),
+            };
+
+            for &amt in amts {
+                let i = ALL_MOVES
+                    .iter()
+                    .position(|m| *m == Move { dir, amt })
+                    .unwrap();
+                if !indices.contains(&i) {
+                    indices.push(i);
+                }
+            }
+        }
+
+        if indices.is_empty() {
+            return Err("No generators given".to_string());
+        }
+
+        indices.sort();
+        Ok(MoveSet { indices })
+    }
+}
+
+impl Display for MoveSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut generators = Vec::new();
+
+        for dir in [Dir::R, Dir::U, Dir::F] {
+            let amts: Vec<Amt> = self
+                .moves()
+                .filter(|m| m.dir == dir)
+                .map(|m| m.amt)
+                .collect();
+
+            match amts.as_slice() {
+                [] => {}
+                [Amt::Two] => generators.push(format!("{}", Move { dir, amt: Amt::Two })),
+                _ => generators.push(format!("{}", Move { dir, amt: Amt::One })),
+            }
+        }
+
+        write!(f, "<{}>", generators.join(","))
+    }
+}
+
 pub fn reversed<'a>(moves: &'a [Move]) -> impl 'a + Iterator<Item = Move> {
     moves.iter().rev().map(|m| m.reversed())
 }
@@ -63,7 +227,7 @@ impl Display for Move {
     }
 }
 
-pub fn nice_write(moves: &[Move]) -> String {
+pub fn nice_write<M: Display>(moves: &[M]) -> String {
     if moves.is_empty() {
         return String::new();
     }
@@ -175,6 +339,189 @@ pub enum FullDir {
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct FullMove(pub FullDir, pub Amt);
 
+impl FullMove {
+    pub fn reversed(self) -> Self {
+        FullMove(self.0, self.1.reversed())
+    }
+}
+
+impl From<Move> for FullMove {
+    fn from(m: Move) -> Self {
+        let dir = match m.dir {
+            Dir::R => FullDir::R,
+            Dir::U => FullDir::U,
+            Dir::F => FullDir::F,
+        };
+
+        FullMove(dir, m.amt)
+    }
+}
+
+impl Display for FullMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.0)?;
+
+        match self.1 {
+            Amt::One => {}
+            Amt::Two => write!(f, "2")?,
+            Amt::Rev => write!(f, "'")?,
+        }
+
+        Ok(())
+    }
+}
+
+/// The same moves, with neighbouring turns of the same face merged (so `R' R U U` is just `U2`)
+pub fn cancelled_full(moves: &[FullMove]) -> Vec<FullMove> {
+    let mut out: Vec<FullMove> = Vec::new();
+
+    for &m in moves {
+        match out.last() {
+            Some(last) if last.0 == m.0 => {
+                let turns = last.1.quarter_turns() + m.1.quarter_turns();
+                out.pop();
+                if let Some(amt) = Amt::from_quarter_turns(turns) {
+                    out.push(FullMove(m.0, amt));
+                }
+            }
+            _ => out.push(m),
+        }
+    }
+
+    out
+}
+
+/// This many clockwise quarter turns of U, as a single move (or none at all); the usual way to
+/// adjust the U face before or after an algorithm
+pub fn u_turns(count: usize) -> Vec<FullMove> {
+    Amt::from_quarter_turns(count)
+        .map(|amt| FullMove(FullDir::U, amt))
+        .into_iter()
+        .collect()
+}
+
+/// An axis to turn the whole cube around, as in the usual notation: x turns the cube the same way
+/// as R, y the same way as U, and z the same way as F
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// A whole cube turn, like `y'`
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Rotation(pub Axis, pub Amt);
+
+impl Rotation {
+    /// The two opposite face moves which, between them, turn the whole cube this way
+    pub fn as_moves(self) -> [FullMove; 2] {
+        let (near, far) = match self.0 {
+            Axis::X => (FullDir::R, FullDir::L),
+            Axis::Y => (FullDir::U, FullDir::D),
+            Axis::Z => (FullDir::F, FullDir::B),
+        };
+
+        [FullMove(near, self.1), FullMove(far, self.1.reversed())]
+    }
+
+    /// Which face ends up being turned, when a turn of `dir` is done with the cube held this way
+    /// round (so after x, turning U really turns what was F)
+    fn conjugate_dir(self, dir: FullDir) -> FullDir {
+        use FullDir::*;
+
+        let quarter = |dir: FullDir| match (self.0, dir) {
+            (Axis::X, U) => F,
+            (Axis::X, F) => D,
+            (Axis::X, D) => B,
+            (Axis::X, B) => U,
+            (Axis::Y, R) => B,
+            (Axis::Y, B) => L,
+            (Axis::Y, L) => F,
+            (Axis::Y, F) => R,
+            (Axis::Z, U) => L,
+            (Axis::Z, L) => D,
+            (Axis::Z, D) => R,
+            (Axis::Z, R) => U,
+            (_, other) => other,
+        };
+
+        (0..self.1.quarter_turns()).fold(dir, |dir, _| quarter(dir))
+    }
+}
+
+impl Display for Rotation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let axis = match self.0 {
+            Axis::X => "x",
+            Axis::Y => "y",
+            Axis::Z => "z",
+        };
+
+        write!(f, "{}", axis)?;
+
+        match self.1 {
+            Amt::One => {}
+            Amt::Two => write!(f, "2")?,
+            Amt::Rev => write!(f, "'")?,
+        }
+
+        Ok(())
+    }
+}
+
+/// The plane a mirror stands in; the L/R mirror swaps the L and R faces over, and so on
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Plane {
+    LR,
+    UD,
+    FB,
+}
+
+/// The sequence as seen in a mirror: the faces on either side of the mirror swap over, and every
+/// turn goes the other way
+pub fn mirrored_full(moves: &[FullMove], plane: Plane) -> Vec<FullMove> {
+    use FullDir::*;
+
+    moves
+        .iter()
+        .map(|&FullMove(dir, amt)| {
+            let dir = match (plane, dir) {
+                (Plane::LR, R) => L,
+                (Plane::LR, L) => R,
+                (Plane::UD, U) => D,
+                (Plane::UD, D) => U,
+                (Plane::FB, F) => B,
+                (Plane::FB, B) => F,
+                (_, other) => other,
+            };
+
+            FullMove(dir, amt.reversed())
+        })
+        .collect()
+}
+
+/// The sequence done with the whole cube turned by `rotations` first, written as seen from the
+/// original grip; the same as doing the rotations, the moves, and then the rotations backwards
+pub fn rotated_full(moves: &[FullMove], rotations: &[Rotation]) -> Vec<FullMove> {
+    moves
+        .iter()
+        .map(|&FullMove(dir, amt)| {
+            let dir = rotations
+                .iter()
+                .rev()
+                .fold(dir, |dir, rotation| rotation.conjugate_dir(dir));
+
+            FullMove(dir, amt)
+        })
+        .collect()
+}
+
+/// The inverse of a sequence of moves; the same as [flipped], but for full moves
+pub fn flipped_full(moves: &[FullMove]) -> Vec<FullMove> {
+    moves.iter().rev().map(|m| m.reversed()).collect()
+}
+
 pub trait CanFullMove: Sized {
     fn apply_full(self, m: FullMove) -> Self;
 
@@ -245,3 +592,18 @@ pub fn flipped(moves: &[Move]) -> Vec<Move> {
 
     out
 }
+
+#[cfg(test)]
+mod moves_tests {
+    use super::*;
+
+    #[test]
+    fn move_sets() {
+        let set: MoveSet = "<R2, U, F2>".parse().unwrap();
+        assert_eq!(set.indices().len(), 5);
+        assert_eq!(set.to_string(), "<R2,U,F2>");
+        assert_eq!("R U".parse::<MoveSet>().unwrap().to_string(), "<R,U>");
+        assert!("<R,L>".parse::<MoveSet>().is_err());
+        assert!("<>".parse::<MoveSet>().is_err());
+    }
+}
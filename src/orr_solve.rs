@@ -1,11 +1,27 @@
 use crate::cube::CubeletOrientationArrangement;
-use crate::moves::{Amt, CanMove, Dir, Move};
+use crate::moves::{CanMove, Move, MoveSet};
 
 /// This uses iterative-bounded DFS (i.e. the stupidest possible IDA* variant) to find an optimal
 /// solution to orientationally solving a pocket cube
 pub fn optimal_solve_orientation(arr: CubeletOrientationArrangement) -> Vec<Move> {
+    // it is known that every pocket cube can be solved in 11 moves so if we can't fix this
+    // there is really something wrong with the cube
+    const MAX_FUEL: usize = 13;
+
+    optimal_solve_orientation_in(arr, &MoveSet::all(), MAX_FUEL)
+        .expect("Everything should be solvable in 11 moves, right")
+}
+
+/// The same search, but only turning the faces in `moves`, and giving up past `max_fuel` moves;
+/// so None if the moves can't solve it, or not that quickly
+pub fn optimal_solve_orientation_in(
+    arr: CubeletOrientationArrangement,
+    moves: &MoveSet,
+    max_fuel: usize,
+) -> Option<Vec<Move>> {
     fn find_solution(
         arr: CubeletOrientationArrangement,
+        moves: &MoveSet,
         running: &mut Vec<Move>,
         fuel: usize,
     ) -> bool {
@@ -17,48 +33,40 @@ pub fn optimal_solve_orientation(arr: CubeletOrientationArrangement) -> Vec<Move
             return false;
         }
 
-        // note we can skip R moves, since they don't alter orientation
-        for dir in [Dir::R, Dir::U, Dir::F] {
-            if running.last().map(|r| r.dir) == Some(dir) {
+        for m in moves.moves() {
+            if running.last().map(|r| r.dir) == Some(m.dir) {
                 continue;
             }
 
-            // amt 1, 2, 3 means "move, move2, move_rev"
-            for amt in [Amt::One, Amt::Two, Amt::Rev] {
-                let m = Move { dir, amt };
-                let moved = arr.clone().apply(m);
-                running.push(m);
-                let found = find_solution(moved, running, fuel - 1);
-                if found {
-                    return true;
-                }
-                running.pop();
+            let moved = arr.clone().apply(m);
+            running.push(m);
+            let found = find_solution(moved, moves, running, fuel - 1);
+            if found {
+                return true;
             }
+            running.pop();
         }
 
         false
     }
 
-    // it is known that every pocket cube can be solved in 11 moves so if we can't fix this
-    // there is really something wrong with the cube
-    const MAX_FUEL: usize = 13;
-
-    for fuel in 0..MAX_FUEL {
+    for fuel in 0..max_fuel {
         let mut running = Vec::with_capacity(fuel);
 
-        let found = find_solution(arr.clone(), &mut running, fuel);
+        let found = find_solution(arr.clone(), moves, &mut running, fuel);
 
         if found {
-            return running;
+            return Some(running);
         }
     }
 
-    unreachable!("Everything should be solvable in 11 moves, right")
+    None
 }
 
 #[cfg(test)]
 mod orr_solve_tests {
     use crate::cube::{Cube, Facelet};
+    use crate::moves::{Amt, Dir};
 
     use super::*;
 
@@ -88,7 +96,7 @@ mod orr_solve_tests {
 
         assert!(running_cube.make_orr_arr_from_dlb().is_solved());
 
-        return soln;
+        soln
     }
 
     #[test]
@@ -0,0 +1,154 @@
+//! Machine-readable output. The human output is prose printed as we go; with `--format json` each
+//! result is instead printed as one JSON object per line, shaped like the structs below.
+
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::dist_table::Metric;
+use crate::moves::Move;
+
+#[derive(ValueEnum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Format {
+    Human,
+    Json,
+}
+
+/// Where the time went, in seconds
+#[derive(Serialize, Default, Debug)]
+pub struct Timings {
+    /// Loading the short-circuit cache (or building the distance table)
+    pub cache_secs: f64,
+    /// Loading the heuristic tables up front, when that's done separately from searching
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heuristic_secs: Option<f64>,
+    pub search_secs: f64,
+}
+
+impl Timings {
+    pub fn new(cache: Duration, search: Duration) -> Self {
+        Timings {
+            cache_secs: cache.as_secs_f64(),
+            heuristic_secs: None,
+            search_secs: search.as_secs_f64(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct SolveOutput {
+    pub input: String,
+    /// The facelet string of the input state
+    pub state: String,
+    pub solution: Vec<String>,
+    pub length: usize,
+    pub cache_size: usize,
+    pub timings: Timings,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ScrambleOutput {
+    /// The moves which make the scramble from a solved cube
+    pub scramble: Vec<String>,
+    /// An optimal solution to the scramble, which is the scramble backwards
+    pub solution: Vec<String>,
+    pub length: usize,
+    /// The facelet string of the scrambled state
+    pub state: String,
+    pub cache_size: usize,
+    pub timings: Timings,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PathOutput {
+    pub from: String,
+    pub to: String,
+    pub solution: Vec<String>,
+    pub length: usize,
+    pub cache_size: usize,
+    pub timings: Timings,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PatternOutput {
+    pub moves: Vec<String>,
+    pub length: usize,
+    /// The facelet string of the pattern
+    pub state: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LengthBoundOutput {
+    pub length_bound: usize,
+    pub cache_depth: usize,
+    pub cache_size: usize,
+    pub states_solved: usize,
+    pub timings: Timings,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AntipodeOutput {
+    /// The facelet string of the state
+    pub state: String,
+    pub solution: Vec<String>,
+    /// How many states are symmetric to this one, itself included
+    pub class_size: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AntipodesOutput {
+    pub metric: Metric,
+    pub distance: usize,
+    /// How many states there are at the distance, and how many classes of symmetric states
+    pub states: usize,
+    pub classes: usize,
+    pub antipodes: Vec<AntipodeOutput>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SubgroupOutput {
+    pub moves: String,
+    pub size: usize,
+    pub diameter: usize,
+    /// How many states there are at each distance from solved
+    pub states: Vec<usize>,
+}
+
+pub fn move_strings(moves: &[Move]) -> Vec<String> {
+    moves.iter().map(|m| m.to_string()).collect()
+}
+
+pub fn print_json<T: Serialize>(value: &T) {
+    println!(
+        "{}",
+        serde_json::to_string(value).expect("Output structs should always serialize")
+    );
+}
+
+#[cfg(test)]
+mod output_tests {
+    use crate::moves::ALL_MOVES;
+
+    use super::*;
+
+    #[test]
+    fn solve_output_shape() {
+        // R U2 F'
+        let moves = [ALL_MOVES[0], ALL_MOVES[4], ALL_MOVES[8]];
+
+        let out = SolveOutput {
+            input: "F U2 R'".to_string(),
+            state: "x".to_string(),
+            solution: move_strings(&moves),
+            length: moves.len(),
+            cache_size: 10,
+            timings: Timings::new(Duration::from_millis(1500), Duration::ZERO),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&out).unwrap(),
+            r#"{"input":"F U2 R'","state":"x","solution":["R","U2","F'"],"length":3,"cache_size":10,"timings":{"cache_secs":1.5,"search_secs":0.0}}"#
+        );
+    }
+}
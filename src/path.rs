@@ -0,0 +1,108 @@
+//! Shortest paths between two arbitrary states, rather than from a state to solved.
+//!
+//! The trick is that the solver doesn't care what the cubies are called. If we rename every cubie
+//! so that the target reads as a solved cube, then solving the renamed start is exactly getting
+//! from the start to the target, and all the usual machinery (heuristics, caches) just works.
+
+use crate::color_scheme::ColorScheme;
+use crate::cube::Cube;
+use crate::full_solve::{optimal_solve_report, HeuristicType, SolveReport};
+use crate::moves::MoveSet;
+use crate::scramble::put_cubie;
+
+/// Renames the cubies of `cube` so that `target` would become `solved`. Each cubie of `target`
+/// takes the name (and twist) of the cubie in the same place on `solved`. None if the two cubes
+/// aren't made of the same cubies.
+pub fn relabel(cube: &Cube, target: &Cube, solved: &Cube) -> Option<Cube> {
+    let target_corners = target.corners();
+    let solved_corners = solved.corners();
+
+    let mut out = solved.clone();
+
+    for (pos, corner) in cube.corners().into_iter().enumerate() {
+        let (source, twist) =
+            (0..8)
+                .flat_map(|p| (0..3).map(move |k| (p, k)))
+                .find(|&(p, k)| {
+                    let mut rotated = target_corners[p].clone();
+                    rotated.rotate_left(k);
+                    rotated == corner
+                })?;
+
+        put_cubie(
+            &mut out,
+            pos as i32,
+            solved_corners[source].clone(),
+            twist as i32,
+        );
+    }
+
+    Some(out)
+}
+
+/// The shortest sequence of moves taking `from` to `to` (up to turning the whole cube, when the
+/// two don't agree on the DLB corner), turning only the faces in `moves`. None if they aren't the
+/// same cube, or the moves can't get from one to the other.
+pub fn optimal_path(
+    from: &Cube,
+    to: &Cube,
+    heuristic_type: HeuristicType,
+    cache_depth: usize,
+    moves: &MoveSet,
+) -> Option<SolveReport> {
+    let relabeled = relabel(from, to, &ColorScheme::western().solved_cube())?;
+
+    optimal_solve_report(relabeled, heuristic_type, cache_depth, moves)
+}
+
+#[cfg(test)]
+mod path_tests {
+    use crate::moves::{nice_write, CanFullMove, CanMove};
+    use crate::setup::parse_line;
+
+    use super::*;
+
+    fn cube(alg: &str) -> Cube {
+        ColorScheme::western()
+            .solved_cube()
+            .apply_many_full(&parse_line(alg).unwrap())
+    }
+
+    #[test]
+    fn relabel_target_is_solved() {
+        let target = cube("R U F'");
+        let solved = ColorScheme::western().solved_cube();
+
+        assert_eq!(relabel(&target, &target, &solved), Some(solved.clone()));
+
+        // relabeling is just renaming, so it commutes with moves
+        let moved = target.clone().apply_many_full(&parse_line("U2 R").unwrap());
+        let relabeled = relabel(&moved, &target, &solved).unwrap();
+        assert_eq!(
+            relabeled,
+            solved.apply_many_full(&parse_line("U2 R").unwrap())
+        );
+    }
+
+    #[test]
+    fn paths_between_patterns() {
+        let from = cube("R U R' U'");
+        let to = cube("R U R' U' F2 R");
+
+        let path = optimal_path(&from, &to, HeuristicType::Orr, 5, &MoveSet::all())
+            .unwrap()
+            .solution;
+        assert_eq!(nice_write(&path), "F2 R");
+        assert_eq!(from.clone().apply_many(&path), to);
+
+        // and back again
+        let back = optimal_path(&to, &from, HeuristicType::Orr, 5, &MoveSet::all())
+            .unwrap()
+            .solution;
+        assert_eq!(nice_write(&back), "R' F2");
+
+        // a japanese cube isn't made of the same cubies
+        let other = ColorScheme::japanese().solved_cube();
+        assert!(optimal_path(&from, &other, HeuristicType::Orr, 5, &MoveSet::all()).is_none());
+    }
+}
@@ -0,0 +1,90 @@
+//! Decorative sticker patterns, as goals for [crate::search::find_states]
+
+use clap::ValueEnum;
+
+use crate::cube::{Cube, Facelet};
+
+/// Some well-known patterns, which only care about how each face looks
+#[derive(ValueEnum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Pattern {
+    /// Every face is a checkerboard of two colors (which, searching the whole state space shows,
+    /// can't actually be done on a pocket cube)
+    Checkerboard,
+    /// Every face is two colors, split down the middle one way or the other
+    Stripes,
+    /// Every face has exactly two colors on it, arranged any way
+    TwoColors,
+}
+
+/// The four facelets of each face, in the order of [Cube::to_facelets]; that is, top left, top
+/// right, bottom left, bottom right as they look in the net
+fn faces(cube: &Cube) -> [[Facelet; 4]; 6] {
+    let facelets = cube.to_facelets();
+
+    std::array::from_fn(|face| std::array::from_fn(|i| facelets[4 * face + i].clone()))
+}
+
+impl Pattern {
+    pub fn matches(self, cube: &Cube) -> bool {
+        faces(cube).iter().all(|[tl, tr, bl, br]| match self {
+            Pattern::Checkerboard => tl == br && tr == bl && tl != tr,
+            Pattern::Stripes => {
+                (tl == tr && bl == br && tl != bl) || (tl == bl && tr == br && tl != tr)
+            }
+            Pattern::TwoColors => {
+                let mut colors = vec![tl, tr, bl, br];
+                colors.sort_by_key(|c| c.letter());
+                colors.dedup();
+                colors.len() == 2
+            }
+        })
+    }
+}
+
+/// Whether the cube matches a facelet template (see [crate::setup::parse_template]); wildcards
+/// match anything
+pub fn matches_template(cube: &Cube, template: &[Option<Facelet>; 24]) -> bool {
+    cube.to_facelets()
+        .iter()
+        .zip(template)
+        .all(|(facelet, want)| want.as_ref().is_none_or(|want| want == facelet))
+}
+
+#[cfg(test)]
+mod pattern_tests {
+    use crate::color_scheme::ColorScheme;
+    use crate::moves::CanFullMove;
+    use crate::setup::{parse_line, parse_template};
+
+    use super::*;
+
+    #[test]
+    fn simple_patterns() {
+        let solved = ColorScheme::western().solved_cube();
+        assert!(!Pattern::TwoColors.matches(&solved));
+
+        // R2 gives stripes on four faces, but U and D stay solid
+        let r2 = solved.clone().apply_many_full(&parse_line("R2").unwrap());
+        assert!(!Pattern::Stripes.matches(&r2));
+
+        // but R2 U2 gives stripes everywhere
+        let striped = solved.apply_many_full(&parse_line("R2 U2").unwrap());
+        assert!(Pattern::Stripes.matches(&striped));
+        assert!(Pattern::TwoColors.matches(&striped));
+        assert!(!Pattern::Checkerboard.matches(&striped));
+    }
+
+    #[test]
+    fn templates() {
+        let solved = ColorScheme::western().solved_cube();
+        let u = solved.clone().apply_many_full(&parse_line("U").unwrap());
+
+        let white_top = parse_template("WWWW ???? ???? ???? ???? ????").unwrap();
+        assert!(matches_template(&solved, &white_top));
+        assert!(matches_template(&u, &white_top));
+
+        let exact = parse_template(&solved.to_facelet_string()).unwrap();
+        assert!(matches_template(&solved, &exact));
+        assert!(!matches_template(&u, &exact));
+    }
+}
@@ -0,0 +1,200 @@
+//! What a state does to the corners, seen as a permutation with twists rather than as facelets;
+//! the view group theory works with. Everything is relative to DLB, the same as the
+//! arrangements it's made from, so a state which is only a whole cube turn away from solved
+//! doesn't do anything.
+
+use crate::cube::{Cube, CubeletOrientationArrangement, CubeletPos, CubeletPositionArrangement};
+
+pub struct CornerPermutation {
+    /// The corner which started at position i (by [CubeletPos::index]) is at `to[i]`
+    to: [usize; 8],
+    /// How twisted each position's corner is, as in [CubeletOrientationArrangement::to_ints]
+    twists: [i32; 8],
+}
+
+/// One cycle of corners, in the order they move round (each to the next, and the last to the
+/// first), starting from the lowest position; a corner twisted in place is a cycle of one
+pub struct CornerCycle {
+    pub corners: Vec<CubeletPos>,
+    /// How far the corners are twisted after going round once (0, 1 or 2); when it isn't 0, it
+    /// takes three times round for them to come back as they were
+    pub twist: i32,
+}
+
+impl CornerCycle {
+    /// How many times round it takes to put everything back
+    pub fn order(&self) -> usize {
+        if self.twist == 0 {
+            self.corners.len()
+        } else {
+            3 * self.corners.len()
+        }
+    }
+}
+
+/// The nontrivial cycles of a position arrangement (see
+/// [crate::cube::CubeletPositionArrangement::to_indices]), or any other map from positions to
+/// positions, each starting from its lowest position
+pub fn position_cycles(positions: &[usize; 8]) -> Vec<Vec<CubeletPos>> {
+    let mut seen = [false; 8];
+    let mut out = Vec::new();
+
+    for start in 0..8 {
+        if seen[start] || positions[start] == start {
+            continue;
+        }
+
+        let mut cycle = Vec::new();
+        let mut pos = start;
+        while !seen[pos] {
+            seen[pos] = true;
+            cycle.push(CubeletPos::from_index(pos));
+            pos = positions[pos];
+        }
+
+        out.push(cycle);
+    }
+
+    out
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl CornerPermutation {
+    pub fn from_arrangements(
+        pos: &CubeletPositionArrangement,
+        orr: &CubeletOrientationArrangement,
+    ) -> Self {
+        // the arrangement says where each position's corner belongs, which is where it came from
+        let mut to = [0; 8];
+        for (at, home) in pos.to_indices().into_iter().enumerate() {
+            to[home] = at;
+        }
+
+        CornerPermutation {
+            to,
+            twists: orr.to_ints(),
+        }
+    }
+
+    /// What the moves which make this cube (from solved) do to the corners
+    pub fn from_cube(cube: &Cube) -> Self {
+        Self::from_arrangements(
+            &cube.clone().make_pos_arr_from_dlb(),
+            &cube.clone().make_orr_arr_from_dlb(),
+        )
+    }
+
+    /// Every cycle which moves or twists something
+    pub fn cycles(&self) -> Vec<CornerCycle> {
+        let twist_of = |corners: &[CubeletPos]| {
+            corners
+                .iter()
+                .map(|pos| self.twists[pos.index()])
+                .sum::<i32>()
+                .rem_euclid(3)
+        };
+
+        let mut out: Vec<CornerCycle> = position_cycles(&self.to)
+            .into_iter()
+            .map(|corners| CornerCycle {
+                twist: twist_of(&corners),
+                corners,
+            })
+            .collect();
+
+        for pos in 0..8 {
+            if self.to[pos] == pos && self.twists[pos] != 0 {
+                out.push(CornerCycle {
+                    corners: vec![CubeletPos::from_index(pos)],
+                    twist: self.twists[pos],
+                });
+            }
+        }
+
+        out
+    }
+
+    /// How many times the state has to be repeated to come back to solved. This is only the same as
+    /// repeating the moves which made it when they leave DLB where it was; see
+    /// [crate::alg::order].
+    pub fn order(&self) -> usize {
+        self.cycles()
+            .iter()
+            .map(CornerCycle::order)
+            .fold(1, |acc, order| acc / gcd(acc, order) * order)
+    }
+
+    /// The cycle structure in words, like "3-cycle + 2 twisted corners"
+    pub fn describe(&self) -> String {
+        let cycles = self.cycles();
+
+        let mut parts: Vec<String> = cycles
+            .iter()
+            .filter(|c| c.corners.len() > 1)
+            .map(|c| match c.twist {
+                0 => format!("{}-cycle", c.corners.len()),
+                _ => format!("twisted {}-cycle", c.corners.len()),
+            })
+            .collect();
+
+        match cycles.iter().filter(|c| c.corners.len() == 1).count() {
+            0 => {}
+            1 => parts.push("1 twisted corner".to_string()),
+            n => parts.push(format!("{} twisted corners", n)),
+        }
+
+        if parts.is_empty() {
+            "nothing".to_string()
+        } else {
+            parts.join(" + ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod permutation_tests {
+    use crate::color_scheme::ColorScheme;
+    use crate::moves::CanFullMove;
+    use crate::setup::parse_line;
+
+    use super::*;
+
+    fn effect(alg: &str) -> CornerPermutation {
+        let cube = ColorScheme::western()
+            .solved_cube()
+            .apply_many_full(&parse_line(alg).unwrap());
+        CornerPermutation::from_cube(&cube)
+    }
+
+    #[test]
+    fn cycle_structures() {
+        assert_eq!(effect("").describe(), "nothing");
+        assert_eq!(effect("R L'").describe(), "nothing");
+        assert_eq!(effect("R").describe(), "4-cycle");
+        assert_eq!(effect("R2 U2").describe(), "3-cycle + 3-cycle");
+
+        // R takes the front corners up, and the top ones round to the back
+        use CubeletPos::*;
+        let r = effect("R").cycles();
+        assert_eq!(r[0].corners, vec![DRF, URF, URB, DRB]);
+
+        // the corner half of a Niklas is a pure 3-cycle
+        assert_eq!(effect("R U' L' U R' U' L U").describe(), "3-cycle");
+    }
+
+    #[test]
+    fn orders_of_states() {
+        assert_eq!(effect("").order(), 1);
+        assert_eq!(effect("R").order(), 4);
+        assert_eq!(effect("R U").order(), 15);
+        assert_eq!(effect("R U R' U'").order(), 6);
+        assert_eq!(effect("R2 U2").order(), 3);
+    }
+}
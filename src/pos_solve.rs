@@ -1,11 +1,27 @@
 use crate::cube::CubeletPositionArrangement;
-use crate::moves::{Amt, CanMove, Dir, Move};
+use crate::moves::{CanMove, Move, MoveSet};
 
 /// This uses iterative-bounded DFS (i.e. the stupidest possible IDA* variant) to find an optimal
 /// solution to positionally solving a pocket cube
 pub fn optimal_solve_position(arr: CubeletPositionArrangement) -> Vec<Move> {
+    // it is known that every pocket cube can be solved in 11 moves so if we can't fix this
+    // there is really something wrong with the cube
+    const MAX_FUEL: usize = 13;
+
+    optimal_solve_position_in(arr, &MoveSet::all(), MAX_FUEL)
+        .expect("Everything should be solvable in 11 moves, right")
+}
+
+/// The same search, but only turning the faces in `moves`, and giving up past `max_fuel` moves;
+/// so None if the moves can't solve it, or not that quickly
+pub fn optimal_solve_position_in(
+    arr: CubeletPositionArrangement,
+    moves: &MoveSet,
+    max_fuel: usize,
+) -> Option<Vec<Move>> {
     fn find_solution(
         arr: CubeletPositionArrangement,
+        moves: &MoveSet,
         running: &mut Vec<Move>,
         fuel: usize,
     ) -> bool {
@@ -17,47 +33,40 @@ pub fn optimal_solve_position(arr: CubeletPositionArrangement) -> Vec<Move> {
             return false;
         }
 
-        for dir in [Dir::R, Dir::U, Dir::F] {
-            if running.last().map(|r| r.dir) == Some(dir) {
+        for m in moves.moves() {
+            if running.last().map(|r| r.dir) == Some(m.dir) {
                 continue;
             }
 
-            // amt 1, 2, 3 means "move, move2, move_rev"
-            for amt in [Amt::One, Amt::Two, Amt::Rev] {
-                let m = Move { dir, amt };
-                let moved = arr.clone().apply(m);
-                running.push(m);
-                let found = find_solution(moved, running, fuel - 1);
-                if found {
-                    return true;
-                }
-                running.pop();
+            let moved = arr.clone().apply(m);
+            running.push(m);
+            let found = find_solution(moved, moves, running, fuel - 1);
+            if found {
+                return true;
             }
+            running.pop();
         }
 
         false
     }
 
-    // it is known that every pocket cube can be solved in 11 moves so if we can't fix this
-    // there is really something wrong with the cube
-    const MAX_FUEL: usize = 13;
-
-    for fuel in 0..MAX_FUEL {
+    for fuel in 0..max_fuel {
         let mut running = Vec::with_capacity(fuel);
 
-        let found = find_solution(arr.clone(), &mut running, fuel);
+        let found = find_solution(arr.clone(), moves, &mut running, fuel);
 
         if found {
-            return running;
+            return Some(running);
         }
     }
 
-    unreachable!("Everything should be solvable in 11 moves, right")
+    None
 }
 
 #[cfg(test)]
 mod pos_solve_tests {
     use crate::cube::{Cube, Facelet};
+    use crate::moves::{Amt, Dir};
 
     use super::*;
 
@@ -87,7 +96,7 @@ mod pos_solve_tests {
 
         assert!(running_cube.make_pos_arr_from_dlb().is_solved());
 
-        return soln;
+        soln
     }
 
     #[test]
@@ -145,4 +154,22 @@ mod pos_solve_tests {
 
         do_pos_solve_test(c);
     }
+
+    #[test]
+    fn restricted_moves() {
+        let c = Cube::make_solved(Facelet::Green, Facelet::Yellow)
+            .right()
+            .up()
+            .right_two();
+        let arr = c.make_pos_arr_from_dlb();
+
+        let two_gen: MoveSet = "<R,U>".parse().unwrap();
+        let soln = optimal_solve_position_in(arr.clone(), &two_gen, 13).unwrap();
+        assert_eq!(soln.len(), 3);
+        assert!(soln.iter().all(|m| m.dir != Dir::F));
+
+        // U turns alone can't put R's corners back
+        let turns: MoveSet = "<U>".parse().unwrap();
+        assert_eq!(optimal_solve_position_in(arr, &turns, 13), None);
+    }
 }
@@ -0,0 +1,371 @@
+//! Drawing cubes for humans to look at.
+
+use std::fmt::{Display, Formatter};
+use std::io::IsTerminal;
+
+use clap::ValueEnum;
+
+use crate::cube::{Cube, CubeletPos, Facelet};
+
+/// 256-color ANSI code for the background of a facelet
+fn ansi_color(facelet: &Facelet) -> u8 {
+    match facelet {
+        Facelet::White => 231,
+        Facelet::Yellow => 226,
+        Facelet::Red => 196,
+        Facelet::Orange => 208,
+        Facelet::Blue => 21,
+        Facelet::Green => 40,
+    }
+}
+
+fn write_facelet(out: &mut String, facelet: &Facelet, color: bool) {
+    if color {
+        out.push_str(&format!("\x1b[48;5;{}m  \x1b[0m", ansi_color(facelet)));
+    } else {
+        out.push(facelet.letter());
+        out.push(' ');
+    }
+}
+
+/// Draws the unfolded net of the cube: U on top, then L F R B in a row, then D on the bottom.
+/// With `color`, each facelet is a block of ANSI background color; otherwise it's the letter of
+/// its color.
+pub fn render_net(cube: &Cube, color: bool) -> String {
+    let facelets = cube.to_facelets();
+
+    // each face is four facelets, in reading order, in the order U R F D L B
+    let face = |i: usize| &facelets[i * 4..i * 4 + 4];
+    let (u, r, f, d, l, b) = (face(0), face(1), face(2), face(3), face(4), face(5));
+
+    let mut out = String::new();
+
+    // U and D sit above and below F, so they're indented by the width of L
+    let rows = [(vec![u], true), (vec![l, f, r, b], false), (vec![d], true)];
+
+    for (faces, indent) in rows {
+        for row in 0..2 {
+            if indent {
+                out.push_str("    ");
+            }
+
+            for face in &faces {
+                write_facelet(&mut out, &face[row * 2], color);
+                write_facelet(&mut out, &face[row * 2 + 1], color);
+            }
+
+            // the plain letters leave a trailing space, which nobody wants
+            let trimmed = out.trim_end_matches(' ').len();
+            out.truncate(trimmed);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+impl Display for Cube {
+    /// Draws the net of the cube; in color if stdout is a terminal, and with plain letters if not
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let color = std::io::stdout().is_terminal();
+
+        write!(f, "{}", render_net(self, color))
+    }
+}
+
+/// Which picture of the cube to draw
+#[derive(ValueEnum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum View {
+    /// The whole unfolded net, like the terminal rendering
+    Net,
+    /// Looking down at the U face, with the top row of each side face around it
+    Top,
+}
+
+/// Which facelets to gray out
+#[derive(ValueEnum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Mask {
+    /// Show everything
+    None,
+    /// Only show the U and D colors, which is all that matters for orientation
+    Orientation,
+    /// Only show the U layer
+    LastLayer,
+}
+
+impl Mask {
+    /// Which facelets (in the order of [Cube::to_facelets]) should be shown in color
+    fn visible(self, cube: &Cube) -> [bool; 24] {
+        match self {
+            Mask::None => [true; 24],
+            Mask::Orientation => {
+                let desired = cube.make_desired_from_dlb();
+                cube.to_facelets()
+                    .map(|facelet| facelet == desired.u || facelet == desired.d)
+            }
+            Mask::LastLayer => {
+                let mut out = [false; 24];
+                // all of U, and the top row of each side
+                for i in [0, 1, 2, 3, 4, 5, 8, 9, 16, 17, 20, 21] {
+                    out[i] = true;
+                }
+                out
+            }
+        }
+    }
+}
+
+/// Hex color of a facelet, in the usual VisualCube colors
+fn svg_color(facelet: &Facelet) -> &'static str {
+    match facelet {
+        Facelet::White => "#ffffff",
+        Facelet::Yellow => "#fefe00",
+        Facelet::Red => "#ee0000",
+        Facelet::Orange => "#ffa100",
+        Facelet::Blue => "#0000f2",
+        Facelet::Green => "#00d800",
+    }
+}
+
+const MASKED_COLOR: &str = "#808080";
+
+/// Size of a full facelet
+const CELL: f64 = 40.0;
+/// Gap between faces (or around the edge)
+const GAP: f64 = 6.0;
+/// Thickness of the side facelets in the top view
+const SIDE: f64 = 12.0;
+
+fn svg_rect(out: &mut String, x: f64, y: f64, w: f64, h: f64, fill: &str) {
+    out.push_str(&format!(
+        "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"#000000\" stroke-width=\"1.5\"/>\n",
+        x, y, w, h, fill
+    ));
+}
+
+/// Center of the U face facelet above the given (U layer) position, relative to the corner of the
+/// U face
+fn u_cell_center(pos: &CubeletPos) -> Option<(f64, f64)> {
+    let (col, row) = match pos {
+        CubeletPos::ULB => (0.0, 0.0),
+        CubeletPos::URB => (1.0, 0.0),
+        CubeletPos::ULF => (0.0, 1.0),
+        CubeletPos::URF => (1.0, 1.0),
+        _ => return None,
+    };
+
+    Some(((col + 0.5) * CELL, (row + 0.5) * CELL))
+}
+
+/// Arrows (drawn on the U face, whose corner is at `origin`) showing where each U layer cubelet
+/// needs to go, according to the position arrangement; cubelets which need to leave the U layer
+/// are skipped, and swaps get a single arrow with two heads.
+fn svg_arrows(out: &mut String, cube: &Cube, origin: (f64, f64)) {
+    let pos_arr = cube.clone().make_pos_arr_from_dlb();
+
+    let moves = [
+        (CubeletPos::ULB, pos_arr.ulb),
+        (CubeletPos::URB, pos_arr.urb),
+        (CubeletPos::ULF, pos_arr.ulf),
+        (CubeletPos::URF, pos_arr.urf),
+    ];
+
+    for (from, to) in &moves {
+        let (Some(start), Some(end)) = (u_cell_center(from), u_cell_center(to)) else {
+            continue;
+        };
+
+        if from == to {
+            continue;
+        }
+
+        let is_swap = moves.iter().any(|(a, b)| a == to && b == from);
+
+        // only draw one arrow for a swap
+        if is_swap && from.index() > to.index() {
+            continue;
+        }
+
+        // pull the ends in a bit so the heads don't pile up in the middle of the facelets
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let len = (dx * dx + dy * dy).sqrt();
+        let shrink = CELL * 0.2 / len;
+
+        let (x1, y1) = (start.0 + dx * shrink, start.1 + dy * shrink);
+        let (x2, y2) = (end.0 - dx * shrink, end.1 - dy * shrink);
+
+        out.push_str(&format!(
+            "  <line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#000000\" stroke-width=\"3\" marker-end=\"url(#arrowhead)\"{}/>\n",
+            origin.0 + x1,
+            origin.1 + y1,
+            origin.0 + x2,
+            origin.1 + y2,
+            if is_swap {
+                " marker-start=\"url(#arrowhead)\""
+            } else {
+                ""
+            }
+        ));
+    }
+}
+
+/// Draws the cube as an SVG image, in the style of VisualCube
+pub fn render_svg(cube: &Cube, view: View, mask: Mask, arrows: bool) -> String {
+    let facelets = cube.to_facelets();
+    let visible = mask.visible(cube);
+
+    let fill = |i: usize| {
+        if visible[i] {
+            svg_color(&facelets[i])
+        } else {
+            MASKED_COLOR
+        }
+    };
+
+    let mut body = String::new();
+
+    let (width, height, u_origin) = match view {
+        View::Net => {
+            let face_size = 2.0 * CELL;
+
+            // (face index in U R F D L B order, column, row) of the net
+            let layout = [
+                (0, 1, 0),
+                (1, 2, 1),
+                (2, 1, 1),
+                (3, 1, 2),
+                (4, 0, 1),
+                (5, 3, 1),
+            ];
+
+            for (face, col, row) in layout {
+                let x = GAP + col as f64 * (face_size + GAP);
+                let y = GAP + row as f64 * (face_size + GAP);
+
+                for i in 0..4 {
+                    let (dx, dy) = ((i % 2) as f64 * CELL, (i / 2) as f64 * CELL);
+                    svg_rect(&mut body, x + dx, y + dy, CELL, CELL, fill(face * 4 + i));
+                }
+            }
+
+            (
+                GAP + 4.0 * (face_size + GAP),
+                GAP + 3.0 * (face_size + GAP),
+                (GAP + face_size + GAP, GAP),
+            )
+        }
+        View::Top => {
+            let u = GAP + SIDE + GAP;
+            let far = u + 2.0 * CELL + GAP;
+
+            for i in 0..4 {
+                let (dx, dy) = ((i % 2) as f64 * CELL, (i / 2) as f64 * CELL);
+                svg_rect(&mut body, u + dx, u + dy, CELL, CELL, fill(i));
+            }
+
+            // the top row of each side face, as it looks from above; (facelet index, x, y)
+            // for the horizontal strips and then the vertical ones
+            let horizontal = [(21, GAP, 0.0), (20, GAP, 1.0), (8, far, 0.0), (9, far, 1.0)];
+            for (i, y, col) in horizontal {
+                svg_rect(&mut body, u + col * CELL, y, CELL, SIDE, fill(i));
+            }
+
+            let vertical = [(16, GAP, 0.0), (17, GAP, 1.0), (5, far, 0.0), (4, far, 1.0)];
+            for (i, x, row) in vertical {
+                svg_rect(&mut body, x, u + row * CELL, SIDE, CELL, fill(i));
+            }
+
+            (far + SIDE + GAP, far + SIDE + GAP, (u, u))
+        }
+    };
+
+    if arrows {
+        svg_arrows(&mut body, cube, u_origin);
+    }
+
+    let mut out = String::new();
+
+    out.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width, height, width, height
+    ));
+    out.push_str("  <defs>\n");
+    out.push_str("    <marker id=\"arrowhead\" viewBox=\"0 0 10 10\" refX=\"8\" refY=\"5\" markerWidth=\"4\" markerHeight=\"4\" orient=\"auto-start-reverse\">\n");
+    out.push_str("      <path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"#000000\"/>\n");
+    out.push_str("    </marker>\n");
+    out.push_str("  </defs>\n");
+    out.push_str(&body);
+    out.push_str("</svg>\n");
+
+    out
+}
+
+#[cfg(test)]
+mod render_tests {
+    use super::*;
+
+    #[test]
+    fn plain_net() {
+        let cube = Cube::make_solved(Facelet::Green, Facelet::White).right();
+
+        let expected = concat!(
+            "    W G\n",
+            "    W G\n",
+            "O O G Y R R W B\n",
+            "O O G Y R R W B\n",
+            "    Y B\n",
+            "    Y B\n",
+        );
+
+        assert_eq!(render_net(&cube, false), expected);
+    }
+
+    #[test]
+    fn color_net() {
+        let cube = Cube::make_solved(Facelet::Green, Facelet::White);
+
+        let rendered = render_net(&cube, true);
+
+        assert_eq!(rendered.lines().count(), 6);
+        assert_eq!(rendered.matches("\x1b[48;5;231m").count(), 4);
+        assert!(!rendered.contains('W'));
+    }
+
+    #[test]
+    fn svg_net() {
+        let cube = Cube::make_solved(Facelet::Green, Facelet::White);
+
+        let svg = render_svg(&cube, View::Net, Mask::None, false);
+
+        assert_eq!(svg.matches("<rect").count(), 24);
+        assert_eq!(svg.matches("#ffffff").count(), 4);
+        assert!(!svg.contains("<line"));
+    }
+
+    #[test]
+    fn svg_top_masked() {
+        let cube = Cube::make_solved(Facelet::Green, Facelet::White);
+
+        let svg = render_svg(&cube, View::Top, Mask::Orientation, false);
+
+        assert_eq!(svg.matches("<rect").count(), 12);
+        // just the U face is white; the sides are all grayed out
+        assert_eq!(svg.matches("#ffffff").count(), 4);
+        assert_eq!(svg.matches(MASKED_COLOR).count(), 8);
+    }
+
+    #[test]
+    fn svg_arrows_for_swaps() {
+        // the T permutation swaps the two right corners of the U layer
+        let cube = crate::setup::parse_cube(
+            "R U R' U' R' F R2 U' R' U' R U R' F'",
+            &crate::color_scheme::ColorScheme::western(),
+        )
+        .unwrap();
+
+        let svg = render_svg(&cube, View::Top, Mask::LastLayer, true);
+
+        assert_eq!(svg.matches("<line").count(), 1);
+        assert!(svg.contains("marker-start"));
+    }
+}
@@ -0,0 +1,248 @@
+//! An interactive session around a single cube, so you can play with a state by hand (apply some
+//! moves, ask for a hint, undo, ...) without paying for the search caches on every command
+
+use std::io::{BufRead, Write};
+
+use clap::ValueEnum;
+
+use crate::color_scheme::ColorScheme;
+use crate::cube::Cube;
+use crate::dist_table::DistanceTable;
+use crate::moves::{nice_write, CanFullMove};
+use crate::scramble::ScrambleKind;
+use crate::setup::{parse_facelets, parse_line};
+
+const HELP: &str = "\
+Commands:
+  <moves>            apply a sequence of moves, like R U R' U'
+  show               draw the current state
+  solve              print an optimal solution from the current state
+  hint               print the next move of an optimal solution
+  reset              go back to the solved cube
+  scramble <kind>    replace the state with a scramble (full, ofl, cfl, oll, cll, cfl-ofl)
+  state <facelets>   replace the state with the given 24 facelets (U R F D L B)
+  undo, redo         step through the history of states
+  help               print this message
+  quit, exit         leave the session
+";
+
+/// What the session wants done after a line of input
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Response {
+    Print(String),
+    Quit,
+}
+
+pub struct Session {
+    scheme: ColorScheme,
+    cube: Cube,
+    undo: Vec<Cube>,
+    redo: Vec<Cube>,
+    /// Built the first time something needs a distance, then kept for the whole session
+    table: Option<DistanceTable>,
+}
+
+impl Session {
+    pub fn new(scheme: ColorScheme) -> Self {
+        Session {
+            cube: scheme.solved_cube(),
+            scheme,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            table: None,
+        }
+    }
+
+    fn table(&mut self) -> &DistanceTable {
+        self.table.get_or_insert_with(DistanceTable::build)
+    }
+
+    /// Replaces the current state, remembering the old one for undo
+    fn set_cube(&mut self, cube: Cube) -> Response {
+        let old = std::mem::replace(&mut self.cube, cube);
+        self.undo.push(old);
+        self.redo.clear();
+
+        Response::Print(format!("{}", self.cube))
+    }
+
+    pub fn handle(&mut self, line: &str) -> Response {
+        let line = line.trim();
+        let (command, rest) = match line.split_once(char::is_whitespace) {
+            Some((command, rest)) => (command, rest.trim()),
+            None => (line, ""),
+        };
+
+        match command {
+            "" => Response::Print(String::new()),
+            "help" => Response::Print(HELP.to_string()),
+            "quit" | "exit" => Response::Quit,
+            "show" => Response::Print(format!("{}", self.cube)),
+            "solve" => {
+                let cube = self.cube.clone();
+                let solution = self.table().solve(&cube);
+
+                Response::Print(format!(
+                    "Optimal solution in {} moves: {}",
+                    solution.len(),
+                    nice_write(&solution)
+                ))
+            }
+            "hint" => {
+                let cube = self.cube.clone();
+                let solution = self.table().solve(&cube);
+
+                match solution.first() {
+                    None => Response::Print("Already solved".to_string()),
+                    Some(m) => Response::Print(format!(
+                        "Try {}, which leaves {} moves to go",
+                        m,
+                        solution.len() - 1
+                    )),
+                }
+            }
+            "reset" => self.set_cube(self.scheme.solved_cube()),
+            "undo" => match self.undo.pop() {
+                None => Response::Print("Nothing to undo".to_string()),
+                Some(cube) => {
+                    let current = std::mem::replace(&mut self.cube, cube);
+                    self.redo.push(current);
+                    Response::Print(format!("{}", self.cube))
+                }
+            },
+            "redo" => match self.redo.pop() {
+                None => Response::Print("Nothing to redo".to_string()),
+                Some(cube) => {
+                    let current = std::mem::replace(&mut self.cube, cube);
+                    self.undo.push(current);
+                    Response::Print(format!("{}", self.cube))
+                }
+            },
+            "scramble" => {
+                let kind = if rest.is_empty() {
+                    Ok(ScrambleKind::Full)
+                } else {
+                    ScrambleKind::from_str(rest, true)
+                };
+
+                match kind {
+                    Ok(kind) => self.set_cube(kind.scramble(&self.scheme)),
+                    Err(_) => Response::Print(format!("Unknown scramble kind {:?}", rest)),
+                }
+            }
+            "state" => match parse_facelets(rest, &self.scheme) {
+                Ok(cube) => self.set_cube(cube),
+                Err(e) => Response::Print(format!("Could not parse facelets {:?}", e)),
+            },
+            _ => match parse_line(line) {
+                Ok(moves) => self.set_cube(self.cube.clone().apply_many_full(&moves)),
+                Err(tok) => {
+                    Response::Print(format!("Unknown command or move {:?}; try \"help\"", tok))
+                }
+            },
+        }
+    }
+}
+
+/// Runs a session on stdin and stdout until the input runs out or the user quits
+pub fn run(scheme: ColorScheme) {
+    let mut session = Session::new(scheme);
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+
+    println!("Type \"help\" for a list of commands");
+
+    loop {
+        print!("> ");
+        let _ = stdout.flush();
+
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+
+        match session.handle(&line) {
+            Response::Quit => break,
+            Response::Print(out) => print!("{}", with_newline(out)),
+        }
+    }
+}
+
+fn with_newline(mut out: String) -> String {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod repl_tests {
+    use crate::moves::CanMove;
+
+    use super::*;
+
+    #[test]
+    fn undo_and_redo() {
+        let mut session = Session::new(ColorScheme::western());
+        let solved = session.cube.clone();
+
+        session.handle("R U");
+        let after = solved.clone().apply_many_full(&parse_line("R U").unwrap());
+        assert_eq!(session.cube, after);
+
+        session.handle("undo");
+        assert_eq!(session.cube, solved);
+        assert_eq!(
+            session.handle("undo"),
+            Response::Print("Nothing to undo".to_string())
+        );
+
+        session.handle("redo");
+        assert_eq!(session.cube, after);
+
+        // a new state throws away the redo history
+        session.handle("undo");
+        session.handle("F");
+        assert_eq!(
+            session.handle("redo"),
+            Response::Print("Nothing to redo".to_string())
+        );
+    }
+
+    #[test]
+    fn state_hint_and_solve() {
+        let mut session = Session::new(ColorScheme::western());
+        let cube = ColorScheme::western()
+            .solved_cube()
+            .apply_many_full(&parse_line("R U2 F'").unwrap());
+
+        session.handle(&format!("state {}", cube.to_facelet_string()));
+        assert_eq!(session.cube, cube);
+
+        assert_eq!(
+            session.handle("hint"),
+            Response::Print("Try F, which leaves 2 moves to go".to_string())
+        );
+
+        let table = session.table().solve(&cube);
+        assert!(cube.apply_many(&table).solved());
+
+        session.handle("reset");
+        assert_eq!(
+            session.handle("hint"),
+            Response::Print("Already solved".to_string())
+        );
+        assert_eq!(session.handle("quit"), Response::Quit);
+    }
+
+    #[test]
+    fn scrambles_in_the_session_scheme() {
+        let scheme = ColorScheme::japanese();
+        let mut session = Session::new(scheme.clone());
+
+        session.handle("scramble");
+        let facelets = session.cube.to_facelet_string();
+        assert_eq!(parse_facelets(&facelets, &scheme), Ok(session.cube.clone()));
+    }
+}
@@ -1,24 +1,61 @@
 //! Idea here is to construct a cube which is scrambled, but which can be legally solved
 
+use clap::{Subcommand, ValueEnum};
 use itertools::Itertools;
+use rand::seq::index::sample;
 use rand::Rng;
 
-use crate::cube::{Cube, Facelet, ALL_CUBIES};
+use crate::color_scheme::ColorScheme;
+use crate::cube::{Cube, Facelet};
+use crate::dist_table::DistanceTable;
+
+#[derive(Subcommand, ValueEnum, Copy, Clone, Debug)]
+pub enum ScrambleKind {
+    /// Performs a full scramble. All permutations are possible.
+    Full,
+    /// Performs an OFL scramble. The bottom layer will be correctly oriented.
+    OFL,
+    /// Performs a CFL scramble. The bottom layer will be completely solved.
+    CFL,
+    /// Performs an OLL scramble. The bottom and top layer will be correctly oriented.
+    OLL,
+    /// Perfectly scrambles a solved cube into a solved state.
+    CLL,
+    /// Leave the bottom layer completely solved, and the top layer oriented correctly
+    // funny naming but it makes clap happy which is all i wanted really
+    #[allow(non_camel_case_types)]
+    CFL_OFL,
+}
+
+impl ScrambleKind {
+    /// A scrambled cube in the colors of the scheme
+    pub fn scramble(self, scheme: &ColorScheme) -> Cube {
+        match self {
+            ScrambleKind::Full => full_scramble(scheme),
+            ScrambleKind::OFL => scramble_ofl(scheme),
+            ScrambleKind::CFL => scramble_cfl(scheme),
+            ScrambleKind::OLL => scramble_oll(scheme),
+            ScrambleKind::CLL => scramble_cll(scheme),
+            ScrambleKind::CFL_OFL => scramble_cfl_oll(scheme),
+        }
+    }
+}
 
-pub fn scramble_ofl() -> Cube {
-    let mut my_cube = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+pub fn scramble_ofl(scheme: &ColorScheme) -> Cube {
+    let mut my_cube = scheme.solved_cube();
 
-    let mut all_cubies: Vec<[Facelet; 3]> = ALL_CUBIES.into_iter().collect();
+    // the first four are the bottom layer, as they sit on a solved cube
+    let mut all_cubies: Vec<[Facelet; 3]> = scheme.corners().into_iter().collect();
 
     let mut rng = rand::thread_rng();
 
-    // take random white cubelets to put into the cube
+    // take random bottom layer cubelets to put into the cube
     put_cubie(&mut my_cube, 0, all_cubies.remove(rng.gen_range(0..4)), 0);
     put_cubie(&mut my_cube, 1, all_cubies.remove(rng.gen_range(0..3)), 0);
     put_cubie(&mut my_cube, 2, all_cubies.remove(rng.gen_range(0..2)), 0);
     put_cubie(&mut my_cube, 3, all_cubies.remove(rng.gen_range(0..1)), 0);
 
-    // then do the yellow cubies at the end
+    // then do the top layer cubies at the end
     for i in 4..7 {
         let ind = rng.gen_range(0..all_cubies.len());
         let cubie = all_cubies.remove(ind);
@@ -43,20 +80,21 @@ pub fn scramble_ofl() -> Cube {
     unreachable!("Really should have found a valid orientation for that last cube")
 }
 
-pub fn scramble_cfl() -> Cube {
-    let mut my_cube = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+pub fn scramble_cfl(scheme: &ColorScheme) -> Cube {
+    let mut my_cube = scheme.solved_cube();
 
-    let mut all_cubies: Vec<[Facelet; 3]> = ALL_CUBIES.into_iter().collect();
+    // the first four are the bottom layer, as they sit on a solved cube
+    let mut all_cubies: Vec<[Facelet; 3]> = scheme.corners().into_iter().collect();
 
     let mut rng = rand::thread_rng();
 
-    // take random white cubelets to put into the cube
+    // take random bottom layer cubelets to put into the cube
     put_cubie(&mut my_cube, 0, all_cubies.remove(0), 0);
     put_cubie(&mut my_cube, 1, all_cubies.remove(0), 0);
     put_cubie(&mut my_cube, 2, all_cubies.remove(0), 0);
     put_cubie(&mut my_cube, 3, all_cubies.remove(0), 0);
 
-    // then do the yellow cubies at the end
+    // then do the top layer cubies at the end
     for i in 4..7 {
         let cubie = all_cubies.remove(rng.gen_range(0..all_cubies.len()));
 
@@ -80,20 +118,21 @@ pub fn scramble_cfl() -> Cube {
     unreachable!("Really should have found a valid orientation for that last cube")
 }
 
-pub fn scramble_oll() -> Cube {
-    let mut my_cube = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+pub fn scramble_oll(scheme: &ColorScheme) -> Cube {
+    let mut my_cube = scheme.solved_cube();
 
-    let mut all_cubies: Vec<[Facelet; 3]> = ALL_CUBIES.into_iter().collect();
+    // the first four are the bottom layer, as they sit on a solved cube
+    let mut all_cubies: Vec<[Facelet; 3]> = scheme.corners().into_iter().collect();
 
     let mut rng = rand::thread_rng();
 
-    // take random white cubelets to put into the cube
+    // take random bottom layer cubelets to put into the cube
     put_cubie(&mut my_cube, 0, all_cubies.remove(rng.gen_range(0..4)), 0);
     put_cubie(&mut my_cube, 1, all_cubies.remove(rng.gen_range(0..3)), 0);
     put_cubie(&mut my_cube, 2, all_cubies.remove(rng.gen_range(0..2)), 0);
     put_cubie(&mut my_cube, 3, all_cubies.remove(rng.gen_range(0..1)), 0);
 
-    // then do the yellow cubies at the end; we'll scramble the order but leave the orientations
+    // then do the top layer cubies at the end; we'll scramble the order but leave the orientations
     // fixed. Which is a bit iffy ... ??
     let mut scrambled_cubies = Vec::with_capacity(4);
     for _ in 0..4 {
@@ -106,11 +145,9 @@ pub fn scramble_oll() -> Cube {
     // layer; we scramble in advance, then do this deterministically, so we should have an
     // unbiased distribution
     for perm in (0..4).permutations(4) {
-        let mut pos = 4;
-        for cubelet_ind in perm {
+        for (pos, cubelet_ind) in (4..).zip(perm) {
             let cubie = scrambled_cubies[cubelet_ind].clone();
             put_cubie(&mut my_cube, pos, cubie, 0);
-            pos += 1;
         }
 
         if my_cube.clone().make_orr_arr_from_dlb().is_solvable() {
@@ -121,20 +158,21 @@ pub fn scramble_oll() -> Cube {
     unreachable!("Really should have found a valid orientation for that last cube")
 }
 
-pub fn scramble_cfl_oll() -> Cube {
-    let mut my_cube = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+pub fn scramble_cfl_oll(scheme: &ColorScheme) -> Cube {
+    let mut my_cube = scheme.solved_cube();
 
-    let mut all_cubies: Vec<[Facelet; 3]> = ALL_CUBIES.into_iter().collect();
+    // the first four are the bottom layer, as they sit on a solved cube
+    let mut all_cubies: Vec<[Facelet; 3]> = scheme.corners().into_iter().collect();
 
     let mut rng = rand::thread_rng();
 
-    // take specific white cubelets to put into the cube, so the bottom is completely perfect
+    // take specific bottom layer cubelets to put into the cube, so the bottom is completely perfect
     put_cubie(&mut my_cube, 0, all_cubies.remove(0), 0);
     put_cubie(&mut my_cube, 1, all_cubies.remove(0), 0);
     put_cubie(&mut my_cube, 2, all_cubies.remove(0), 0);
     put_cubie(&mut my_cube, 3, all_cubies.remove(0), 0);
 
-    // then do the yellow cubies at the end; we'll scramble the order but leave the orientations
+    // then do the top layer cubies at the end; we'll scramble the order but leave the orientations
     // fixed. Which is a bit iffy ... ??
     let mut scrambled_cubies = Vec::with_capacity(4);
     for _ in 0..4 {
@@ -147,11 +185,9 @@ pub fn scramble_cfl_oll() -> Cube {
     // layer; we scramble in advance, then do this deterministically, so we should have an
     // unbiased distribution
     for perm in (0..4).permutations(4) {
-        let mut pos = 4;
-        for cubelet_ind in perm {
+        for (pos, cubelet_ind) in (4..).zip(perm) {
             let cubie = scrambled_cubies[cubelet_ind].clone();
             put_cubie(&mut my_cube, pos, cubie, 0);
-            pos += 1;
         }
 
         if my_cube.clone().make_orr_arr_from_dlb().is_solvable() {
@@ -162,18 +198,19 @@ pub fn scramble_cfl_oll() -> Cube {
     unreachable!("Really should have found a valid orientation for that last cube")
 }
 
-pub fn scramble_cll() -> Cube {
-    let mut my_cube = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+pub fn scramble_cll(scheme: &ColorScheme) -> Cube {
+    let mut my_cube = scheme.solved_cube();
 
-    let mut all_cubies: Vec<[Facelet; 3]> = ALL_CUBIES.into_iter().collect();
+    // the first four are the bottom layer, as they sit on a solved cube
+    let mut all_cubies: Vec<[Facelet; 3]> = scheme.corners().into_iter().collect();
 
-    // take specific white cubelets to put into the cube
+    // take specific bottom layer cubelets to put into the cube
     put_cubie(&mut my_cube, 0, all_cubies.remove(0), 0);
     put_cubie(&mut my_cube, 1, all_cubies.remove(0), 0);
     put_cubie(&mut my_cube, 2, all_cubies.remove(0), 0);
     put_cubie(&mut my_cube, 3, all_cubies.remove(0), 0);
 
-    // then do specific yellow cubies at the end
+    // then do specific top layer cubies at the end
     put_cubie(&mut my_cube, 4, all_cubies.remove(0), 0);
     put_cubie(&mut my_cube, 5, all_cubies.remove(0), 0);
     put_cubie(&mut my_cube, 6, all_cubies.remove(0), 0);
@@ -182,10 +219,11 @@ pub fn scramble_cll() -> Cube {
     my_cube
 }
 
-pub fn full_scramble() -> Cube {
-    let mut my_cube = Cube::make_solved(Facelet::Green, Facelet::White);
+pub fn full_scramble(scheme: &ColorScheme) -> Cube {
+    let mut my_cube = scheme.solved_cube();
 
-    let mut all_cubies: Vec<[Facelet; 3]> = ALL_CUBIES.into_iter().collect();
+    // the first four are the bottom layer, as they sit on a solved cube
+    let mut all_cubies: Vec<[Facelet; 3]> = scheme.corners().into_iter().collect();
 
     let mut rng = rand::thread_rng();
 
@@ -213,6 +251,24 @@ pub fn full_scramble() -> Cube {
     unreachable!("Really should have found a valid orientation for that last cube")
 }
 
+/// Picks `count` different states, uniformly at random among all the states whose optimal solution
+/// is exactly `distance` moves long. If there aren't that many such states, returns all of them.
+pub fn scramble_at_distance(
+    table: &DistanceTable,
+    scheme: &ColorScheme,
+    distance: usize,
+    count: usize,
+) -> Vec<Cube> {
+    let candidates = table.states_at_distance(distance);
+
+    let mut rng = rand::thread_rng();
+
+    sample(&mut rng, candidates.len(), count.min(candidates.len()))
+        .into_iter()
+        .map(|ind| table.cube_for_state(candidates[ind], scheme))
+        .collect()
+}
+
 pub fn put_cubie(cube: &mut Cube, pos_index: i32, mut cubie: [Facelet; 3], orientation: i32) {
     for _ in 0..orientation {
         cubie.rotate_left(1);
@@ -302,3 +358,27 @@ fn put_urf(cube: &mut Cube, cubie: [Facelet; 3]) {
     cube.r.uf = y;
     cube.f.ur = z;
 }
+
+#[cfg(test)]
+mod scramble_tests {
+    use crate::setup::parse_cube;
+
+    use super::*;
+
+    #[test]
+    fn scrambles_in_other_schemes() {
+        let scheme = ColorScheme::japanese();
+
+        for kind in ScrambleKind::value_variants() {
+            let cube = kind.scramble(&scheme);
+            let parsed = parse_cube(&cube.to_facelet_string(), &scheme).unwrap();
+            assert_eq!(parsed, cube);
+        }
+
+        let table = DistanceTable::build();
+        for cube in scramble_at_distance(&table, &scheme, 6, 3) {
+            let parsed = parse_cube(&cube.to_facelet_string(), &scheme).unwrap();
+            assert_eq!(table.distance(&parsed), 6);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Breadth-first search for states satisfying an arbitrary goal, when "solved" isn't the goal.
+//! There's no heuristic for a general predicate, so this just walks outward from the start, one
+//! layer at a time, skipping states it has already seen.
+
+use std::collections::HashSet;
+
+use crate::cube::Cube;
+use crate::moves::{CanMove, Move, ALL_MOVES};
+
+/// A state which satisfies the goal, and a shortest sequence reaching it
+pub struct Found {
+    pub moves: Vec<Move>,
+    pub cube: Cube,
+}
+
+/// Finds up to `limit` different states satisfying `goal`, within `max_length` moves of `start`,
+/// shortest first. The start itself counts, if it satisfies the goal.
+pub fn find_states<G: Fn(&Cube) -> bool>(
+    start: &Cube,
+    goal: G,
+    max_length: usize,
+    limit: usize,
+) -> Vec<Found> {
+    let mut out = Vec::new();
+    let mut seen: HashSet<Cube> = HashSet::new();
+
+    seen.insert(start.clone());
+    let mut frontier = vec![(start.clone(), Vec::new())];
+
+    for length in 0..=max_length {
+        let mut next_frontier = Vec::new();
+
+        for (cube, moves) in frontier {
+            if goal(&cube) {
+                out.push(Found {
+                    moves: moves.clone(),
+                    cube: cube.clone(),
+                });
+
+                if out.len() >= limit {
+                    return out;
+                }
+            }
+
+            if length == max_length {
+                continue;
+            }
+
+            for m in ALL_MOVES {
+                if moves.last().map(|last: &Move| last.dir) == Some(m.dir) {
+                    continue;
+                }
+
+                let next = cube.clone().apply(m);
+                if seen.insert(next.clone()) {
+                    let mut next_moves = moves.clone();
+                    next_moves.push(m);
+                    next_frontier.push((next, next_moves));
+                }
+            }
+        }
+
+        frontier = next_frontier;
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod search_tests {
+    use crate::color_scheme::ColorScheme;
+    use crate::moves::nice_write;
+
+    use super::*;
+
+    #[test]
+    fn finds_shortest_first() {
+        let solved = ColorScheme::western().solved_cube();
+
+        // states with the U face solved but not the whole cube; the U moves come first
+        let found = find_states(&solved, |c| c.u.solved() && !c.solved(), 2, 5);
+        let moves: Vec<String> = found.iter().map(|f| nice_write(&f.moves)).collect();
+
+        assert_eq!(moves, vec!["U", "U2", "U'"]);
+
+        // the start counts too
+        let found = find_states(&solved, |c| c.solved(), 3, 10);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].moves.is_empty());
+    }
+}
@@ -1,6 +1,12 @@
+use crate::color_scheme::{same_cubie, ColorScheme};
+use crate::cube::{Cube, CubeletPos, Facelet};
+use crate::masked::MaskedCube;
 use crate::moves::Amt;
+use crate::moves::Axis;
+use crate::moves::CanFullMove;
 use crate::moves::FullDir;
 use crate::moves::FullMove;
+use crate::moves::Rotation;
 
 pub fn parse_line(input: &str) -> Result<Vec<FullMove>, &str> {
     let mut out = Vec::new();
@@ -13,6 +19,168 @@ pub fn parse_line(input: &str) -> Result<Vec<FullMove>, &str> {
     Ok(out)
 }
 
+/// Reads whole cube turns like `x y2 z'`
+pub fn parse_rotations(input: &str) -> Result<Vec<Rotation>, &str> {
+    let mut out = Vec::new();
+
+    for tok in input.split_ascii_whitespace() {
+        let (axis, amt) = tok.split_at(1.min(tok.len()));
+
+        let axis = match axis {
+            "x" => Axis::X,
+            "y" => Axis::Y,
+            "z" => Axis::Z,
+            _ => return Err(tok),
+        };
+
+        let amt = match amt {
+            "" => Amt::One,
+            "2" => Amt::Two,
+            "'" => Amt::Rev,
+            _ => return Err(tok),
+        };
+
+        out.push(Rotation(axis, amt));
+    }
+
+    Ok(out)
+}
+
+/// Everything that can be wrong with a facelet string
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum FaceletError {
+    /// There should be exactly 24 facelets (whitespace is ignored)
+    WrongLength(usize),
+    /// Not one of the color letters
+    BadColor(char),
+    /// The facelets at this position don't make up a corner in the color scheme
+    BadCorner(CubeletPos, [Facelet; 3]),
+    /// This corner appears more than once
+    RepeatedCorner([Facelet; 3]),
+    /// Every cubelet is in the right place, but one of them has been twisted in place
+    TwistedCorner,
+    /// Some facelets are unknown, but no real cube matches the ones which are known
+    NoCompatibleCube,
+}
+
+/// Reads a facelet string (as written by [Cube::to_facelet_string]) and checks that it's actually
+/// a solvable cube in the given color scheme
+pub fn parse_facelets(input: &str, scheme: &ColorScheme) -> Result<Cube, FaceletError> {
+    let chars: Vec<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+
+    if chars.len() != 24 {
+        return Err(FaceletError::WrongLength(chars.len()));
+    }
+
+    let facelets: Vec<Facelet> = chars
+        .into_iter()
+        .map(Facelet::try_from)
+        .collect::<Result<_, _>>()
+        .map_err(FaceletError::BadColor)?;
+
+    let facelets: [Facelet; 24] = facelets.try_into().expect("Length was already checked");
+
+    let cube = Cube::from_facelets(facelets);
+    let corners = cube.corners();
+
+    for (i, corner) in corners.iter().enumerate() {
+        if !scheme.is_corner(corner) {
+            return Err(FaceletError::BadCorner(
+                CubeletPos::from_index(i),
+                corner.clone(),
+            ));
+        }
+
+        if corners[..i].iter().any(|other| same_cubie(corner, other)) {
+            return Err(FaceletError::RepeatedCorner(corner.clone()));
+        }
+    }
+
+    if !cube.clone().make_orr_arr_from_dlb().is_solvable() {
+        return Err(FaceletError::TwistedCorner);
+    }
+
+    Ok(cube)
+}
+
+/// Reads a facelet template: a facelet string where any facelet may be a wildcard (`?`, `.` or
+/// `X`) instead of a color. Since it's only a pattern, there's no checking that it's a real cube.
+pub fn parse_template(input: &str) -> Result<[Option<Facelet>; 24], FaceletError> {
+    let chars: Vec<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+
+    if chars.len() != 24 {
+        return Err(FaceletError::WrongLength(chars.len()));
+    }
+
+    let facelets: Vec<Option<Facelet>> = chars
+        .into_iter()
+        .map(|c| match c {
+            '?' | '.' | 'X' => Ok(None),
+            c => Facelet::try_from(c).map(Some),
+        })
+        .collect::<Result<_, _>>()
+        .map_err(FaceletError::BadColor)?;
+
+    Ok(facelets.try_into().expect("Length was already checked"))
+}
+
+/// Reads a facelet string where some facelets may be unknown (`?` or `X`, as in
+/// [parse_template]), and fills in whatever the known ones force
+pub fn parse_masked(input: &str, scheme: &ColorScheme) -> Result<MaskedCube, FaceletError> {
+    let facelets = parse_template(input)?;
+
+    MaskedCube { facelets }
+        .completed(scheme)
+        .ok_or(FaceletError::NoCompatibleCube)
+}
+
+/// Everything that can be wrong with a cube input
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum CubeInputError<'a> {
+    BadMove(&'a str),
+    BadFacelets(FaceletError),
+}
+
+/// Reads a cube from either a sequence of moves (applied to the solved cube of the color scheme)
+/// or a facelet string
+pub fn parse_cube<'a>(input: &'a str, scheme: &ColorScheme) -> Result<Cube, CubeInputError<'a>> {
+    match parse_line(input) {
+        Ok(moves) => Ok(scheme.solved_cube().apply_many_full(&moves)),
+        Err(_) if looks_like_facelets(input, false) => {
+            parse_facelets(input, scheme).map_err(CubeInputError::BadFacelets)
+        }
+        Err(bad_move) => Err(CubeInputError::BadMove(bad_move)),
+    }
+}
+
+/// Reads a masked cube from either a sequence of moves (applied to the solved cube of the color
+/// scheme, so nothing is unknown) or a facelet string with unknown facelets, as in [parse_masked]
+pub fn parse_masked_cube<'a>(
+    input: &'a str,
+    scheme: &ColorScheme,
+) -> Result<MaskedCube, CubeInputError<'a>> {
+    match parse_line(input) {
+        Ok(moves) => Ok(MaskedCube::from_cube(
+            &scheme.solved_cube().apply_many_full(&moves),
+        )),
+        Err(_) if looks_like_facelets(input, true) => {
+            parse_masked(input, scheme).map_err(CubeInputError::BadFacelets)
+        }
+        Err(bad_move) => Err(CubeInputError::BadMove(bad_move)),
+    }
+}
+
+/// Whether the input is a facelet string (with unknown facelets too, if `masked`) rather than
+/// moves which didn't parse. A facelet string is all colors, and every scheme has colors which
+/// aren't moves, so there's no confusing the two.
+fn looks_like_facelets(input: &str, masked: bool) -> bool {
+    input.chars().all(|c| {
+        c.is_whitespace()
+            || Facelet::try_from(c).is_ok()
+            || (masked && matches!(c, '?' | '.' | 'X'))
+    })
+}
+
 impl<'a> TryFrom<&'a str> for FullMove {
     type Error = &'a str;
 
@@ -49,3 +217,123 @@ impl<'a> TryFrom<&'a str> for FullMove {
         }
     }
 }
+
+#[cfg(test)]
+mod setup_tests {
+    use crate::scramble::full_scramble;
+
+    use super::*;
+
+    #[test]
+    fn facelet_round_trip() {
+        for _ in 0..20 {
+            let cube = full_scramble(&ColorScheme::western());
+            let parsed = parse_facelets(&cube.to_facelet_string(), &ColorScheme::western());
+
+            assert_eq!(parsed, Ok(cube));
+        }
+    }
+
+    #[test]
+    fn facelets_in_other_schemes() {
+        let scheme = ColorScheme::japanese();
+        let cube = parse_cube("R U F' L2 D", &scheme).unwrap();
+
+        let text = cube.to_facelet_string();
+        assert_eq!(parse_facelets(&text, &scheme), Ok(cube));
+
+        assert!(matches!(
+            parse_facelets(&text, &ColorScheme::western()),
+            Err(FaceletError::BadCorner(_, _))
+        ));
+    }
+
+    #[test]
+    fn bad_facelets() {
+        let scheme = ColorScheme::western();
+
+        assert_eq!(
+            parse_facelets("WWWW RRRR GGGG YYYY OOOO BBB", &scheme),
+            Err(FaceletError::WrongLength(23))
+        );
+        assert_eq!(
+            parse_facelets("WWWW RRRR GGGG YYYY OOOO BBBX", &scheme),
+            Err(FaceletError::BadColor('X'))
+        );
+        assert_eq!(
+            parse_facelets("WWWW RRRR GGGG YYYY OOOO BBBB", &scheme),
+            Ok(Cube::make_solved(Facelet::Green, Facelet::White))
+        );
+
+        // swap two stickers on the same corner, so it turns inside out
+        let mut facelets = scheme.solved_cube().to_facelets();
+        facelets.swap(3, 4);
+        assert!(matches!(
+            parse_facelets(&Cube::from_facelets(facelets).to_facelet_string(), &scheme),
+            Err(FaceletError::BadCorner(CubeletPos::URF, _))
+        ));
+
+        // twist one corner in place
+        let mut cube = scheme.solved_cube();
+        let Cube { u, r, f, .. } = &mut cube;
+        std::mem::swap(&mut u.fr, &mut r.uf);
+        std::mem::swap(&mut u.fr, &mut f.ur);
+        assert_eq!(
+            parse_facelets(&cube.to_facelet_string(), &scheme),
+            Err(FaceletError::TwistedCorner)
+        );
+    }
+
+    #[test]
+    fn moves_or_facelets() {
+        let scheme = ColorScheme::western();
+
+        let by_moves = parse_cube("R U R' U'", &scheme).unwrap();
+        let by_facelets = parse_cube(&by_moves.to_facelet_string(), &scheme).unwrap();
+        assert_eq!(by_moves, by_facelets);
+
+        assert_eq!(
+            parse_cube("R U X", &scheme),
+            Err(CubeInputError::BadMove("X"))
+        );
+    }
+
+    #[test]
+    fn masked_facelets() {
+        let scheme = ColorScheme::western();
+        let cube = parse_cube("R U F'", &scheme).unwrap();
+
+        // hiding one facelet of each of two corners doesn't lose anything
+        let mut text: Vec<char> = cube.to_facelet_string().chars().collect();
+        text[0] = 'X';
+        text[12] = '?';
+        let text: String = text.into_iter().collect();
+
+        let masked = parse_masked(&text, &scheme).unwrap();
+        assert!(masked.is_known());
+        assert_eq!(masked, MaskedCube::from_cube(&cube));
+
+        assert_eq!(
+            parse_masked("WWWW WWWW ???? ???? ???? ????", &scheme),
+            Err(FaceletError::NoCompatibleCube)
+        );
+    }
+
+    #[test]
+    fn moves_or_masked_facelets() {
+        let scheme = ColorScheme::western();
+        let cube = parse_cube("R U F'", &scheme).unwrap();
+
+        let by_moves = parse_masked_cube("R U F'", &scheme).unwrap();
+        assert_eq!(by_moves, MaskedCube::from_cube(&cube));
+
+        let text = cube.to_facelet_string().replacen(|c| c != ' ', "?", 1);
+        assert_eq!(parse_masked_cube(&text, &scheme).unwrap(), by_moves);
+
+        // a typo in the moves is about the moves, not the length of a facelet string
+        assert_eq!(
+            parse_masked_cube("R U Q", &scheme),
+            Err(CubeInputError::BadMove("Q"))
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! The states reachable from solved turning only some of the faces, like <R,U>, <U,R2,F2> or
+//! <R,U2>. The distance table walk does the work: starting from solved, it only ever reaches
+//! states in the subgroup, and leaves the rest unknown.
+
+use crate::cube::{Cube, CubeletOrientationArrangement, CubeletPositionArrangement};
+use crate::dist_table::{rank_cube, rank_state, DistanceTable, NUM_STATES};
+use crate::moves::MoveSet;
+
+pub struct Subgroup {
+    pub moves: MoveSet,
+    table: DistanceTable,
+    /// How many states there are at each distance from solved
+    histogram: Vec<usize>,
+}
+
+impl Subgroup {
+    pub fn explore(moves: MoveSet) -> Self {
+        let solved = rank_state(
+            &CubeletPositionArrangement::make_solved(),
+            &CubeletOrientationArrangement::make_solved(),
+        );
+
+        let table = DistanceTable::build_restricted(&moves, &[solved]);
+        let histogram = table.histogram();
+
+        Subgroup {
+            moves,
+            table,
+            histogram,
+        }
+    }
+
+    /// How many moves from the set it takes to solve the cube (with DLB fixed), if they can
+    pub fn distance(&self, cube: &Cube) -> Option<usize> {
+        let distance = self.table.state_distance(rank_cube(cube));
+        (distance < self.histogram.len()).then_some(distance)
+    }
+
+    pub fn contains(&self, cube: &Cube) -> bool {
+        self.distance(cube).is_some()
+    }
+
+    pub fn size(&self) -> usize {
+        self.histogram.iter().sum()
+    }
+
+    /// The size as a fraction of the whole state space
+    pub fn fraction(&self) -> f64 {
+        self.size() as f64 / NUM_STATES as f64
+    }
+
+    /// The longest optimal solution of anything in the subgroup
+    pub fn diameter(&self) -> usize {
+        self.histogram.len() - 1
+    }
+
+    /// How many states there are at each distance from solved
+    pub fn histogram(&self) -> &[usize] {
+        &self.histogram
+    }
+}
+
+#[cfg(test)]
+mod subgroup_tests {
+    use crate::color_scheme::ColorScheme;
+    use crate::moves::CanFullMove;
+    use crate::setup::parse_line;
+
+    use super::*;
+
+    fn explore(moves: &str) -> Subgroup {
+        Subgroup::explore(moves.parse().unwrap())
+    }
+
+    #[test]
+    fn whole_cube() {
+        let group = explore("<R,U,F>");
+        assert_eq!(group.size(), NUM_STATES);
+        assert_eq!(group.diameter(), 11);
+    }
+
+    #[test]
+    fn small_subgroups() {
+        let turns = explore("<U>");
+        assert_eq!(turns.histogram(), &[1, 3]);
+
+        let two_gen = explore("<R,U>");
+        assert_eq!(two_gen.size(), 29160);
+        assert_eq!(two_gen.histogram()[1], 6);
+
+        // half turns never twist anything
+        let half_turns = explore("<R2,U2,F2>");
+        assert_eq!(half_turns.size(), 24);
+        assert_eq!(half_turns.diameter(), 4);
+    }
+
+    #[test]
+    fn membership() {
+        let group = explore("<R,U>");
+        let solved = ColorScheme::western().solved_cube();
+
+        let sune = solved
+            .clone()
+            .apply_many_full(&parse_line("R U R' U R U2 R'").unwrap());
+        assert!(group.contains(&sune));
+        assert_eq!(group.distance(&sune), Some(7));
+
+        let sexy = solved
+            .clone()
+            .apply_many_full(&parse_line("R U R' U'").unwrap());
+        assert_eq!(group.distance(&sexy), Some(4));
+
+        let with_f = solved.apply_many_full(&parse_line("R U F").unwrap());
+        assert!(!group.contains(&with_f));
+        assert_eq!(group.distance(&with_f), None);
+    }
+}
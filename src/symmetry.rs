@@ -0,0 +1,430 @@
+//! The symmetries of the cube: the 24 ways to turn it around, and the 24 more you get by also
+//! looking at it in a mirror. Conjugating a state by a symmetry (turn the cube, do the state,
+//! turn it back) gives a state which is exactly as hard to solve, so a lot of questions about
+//! the whole state space only need asking once per class of symmetric states.
+
+use std::collections::VecDeque;
+
+use crate::cube::{
+    corner_turn, Cube, CubeletOrientationArrangement, CubeletPositionArrangement, Facelet,
+};
+use crate::dist_table::{rank_cube, unrank_orr, unrank_pos, NUM_ORIENTATIONS, NUM_STATES};
+use crate::masked::facelet_sources_full;
+use crate::moves::Plane;
+use crate::scramble::put_cubie;
+use crate::setup::parse_line;
+
+/// A symmetry, as what it does to the facelet positions of [Cube::to_facelets]
+#[derive(Clone, Debug)]
+pub struct Symmetry {
+    /// The facelet at `i` goes to `map[i]`
+    map: [usize; 24],
+    mirrored: bool,
+    name: String,
+}
+
+/// Swaps the L and R faces over, as seen in a mirror standing to one side of the cube
+const LR_MIRROR: [usize; 24] = [
+    1, 0, 3, 2, 17, 16, 19, 18, 9, 8, 11, 10, 13, 12, 15, 14, 5, 4, 7, 6, 21, 20, 23, 22,
+];
+
+/// The whole cube turns the others are made of, and the opposite face moves which do them
+const TURNS: [(&str, &str); 9] = [
+    ("x", "R L'"),
+    ("x2", "R2 L2"),
+    ("x'", "R' L"),
+    ("y", "U D'"),
+    ("y2", "U2 D2"),
+    ("y'", "U' D"),
+    ("z", "F B'"),
+    ("z2", "F2 B2"),
+    ("z'", "F' B"),
+];
+
+impl Symmetry {
+    fn identity() -> Self {
+        Symmetry {
+            map: std::array::from_fn(|i| i),
+            mirrored: false,
+            name: String::new(),
+        }
+    }
+
+    /// The whole cube turn made of these (opposite face) moves
+    fn turn_by(name: &str, alg: &str) -> Self {
+        let sources = facelet_sources_full(&parse_line(alg).unwrap());
+
+        let mut map = [0; 24];
+        for (i, &source) in sources.iter().enumerate() {
+            map[source] = i;
+        }
+
+        Symmetry {
+            map,
+            mirrored: false,
+            name: name.to_string(),
+        }
+    }
+
+    /// Doing this and then `other`
+    fn then(&self, other: &Symmetry) -> Self {
+        let name = [self.name.as_str(), other.name.as_str()]
+            .into_iter()
+            .filter(|n| !n.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Symmetry {
+            map: self.map.map(|i| other.map[i]),
+            mirrored: self.mirrored != other.mirrored,
+            name,
+        }
+    }
+
+    /// How to do this: whole cube turns (x, y and z, as in the usual notation), maybe after
+    /// swapping L and R over in a mirror; empty for doing nothing at all
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The symmetry which undoes this one
+    pub fn inverse(&self) -> Symmetry {
+        let mut map = [0; 24];
+        for (i, &to) in self.map.iter().enumerate() {
+            map[to] = i;
+        }
+
+        all_symmetries()
+            .into_iter()
+            .find(|s| s.map == map)
+            .expect("The symmetries are a group")
+    }
+
+    /// Whether this keeps every one of these facelet positions among themselves
+    pub fn preserves(&self, facelets: &[usize]) -> bool {
+        facelets.iter().all(|i| facelets.contains(&self.map[*i]))
+    }
+
+    /// Picks the whole cube up and turns it (or holds it up to the mirror): every facelet moves
+    /// to where the symmetry takes it, keeping its color
+    pub fn turn(&self, cube: &Cube) -> Cube {
+        let facelets = cube.to_facelets();
+        let mut out = facelets.clone();
+        for (i, facelet) in facelets.into_iter().enumerate() {
+            out[self.map[i]] = facelet;
+        }
+
+        Cube::from_facelets(out)
+    }
+
+    /// Repaints every facelet, without moving it, with the color of the face the symmetry takes
+    /// its color's face to (going by the DLB corner for which face is which color)
+    pub fn recolor(&self, cube: &Cube) -> Cube {
+        let desired = cube.make_desired_from_dlb();
+        let reference = Cube::from_faces(
+            desired.u, desired.d, desired.r, desired.l, desired.f, desired.b,
+        )
+        .to_facelets();
+
+        // the faces are four facelets each, so the first facelet of a face stands for its color
+        let recolored: Vec<(Facelet, Facelet)> = (0..6)
+            .map(|face| {
+                let home = 4 * face;
+                (reference[home].clone(), reference[self.map[home]].clone())
+            })
+            .collect();
+
+        Cube::from_facelets(cube.to_facelets().map(|facelet| {
+            let (_, color) = recolored.iter().find(|(from, _)| *from == facelet).unwrap();
+            color.clone()
+        }))
+    }
+
+    /// Conjugates the state by this symmetry: turns the cube and repaints it, so it's the same
+    /// colors as before but the state is seen from somewhere else. A solved cube stays solved,
+    /// and a cube some number of moves from solved ends up the same number of (turned, maybe
+    /// mirrored) moves away.
+    pub fn apply(&self, cube: &Cube) -> Cube {
+        self.turn(&self.recolor(cube))
+    }
+
+    /// The same as [Symmetry::apply], for a state given by its arrangements. The two have to go
+    /// together, since a symmetry which moves the DLB corner can twist it, and then what counts
+    /// as oriented (and where everything else goes) is measured from somewhere new.
+    pub fn apply_arrangements(
+        &self,
+        pos: &CubeletPositionArrangement,
+        orr: &CubeletOrientationArrangement,
+    ) -> (CubeletPositionArrangement, CubeletOrientationArrangement) {
+        let turned = self.apply(&arrangements_cube(pos, orr));
+
+        (
+            turned.clone().make_pos_arr_from_dlb(),
+            turned.make_orr_arr_from_dlb(),
+        )
+    }
+}
+
+/// A cube (in the default colors) in the state with these arrangements
+pub fn arrangements_cube(
+    pos: &CubeletPositionArrangement,
+    orr: &CubeletOrientationArrangement,
+) -> Cube {
+    let mut cube = Cube::make_solved(Facelet::Green, Facelet::White);
+    let corners = cube.corners();
+
+    let positions = pos.to_indices();
+    let orientations = orr.to_ints();
+
+    for (at, (&cubie, &twist)) in positions.iter().zip(orientations.iter()).enumerate() {
+        let orientation = crate::cube::CubeletOrientation::from_int(twist);
+        let turn = corner_turn(at, cubie, &orientation);
+        put_cubie(&mut cube, at as i32, corners[cubie].clone(), turn as i32);
+    }
+
+    cube
+}
+
+/// All the turns, named by their shortest spelling in x, y and z, starting with doing nothing
+fn turns_from(start: Symmetry) -> Vec<Symmetry> {
+    let generators: Vec<Symmetry> = TURNS
+        .iter()
+        .map(|(name, alg)| Symmetry::turn_by(name, alg))
+        .collect();
+
+    let mut out = vec![start.clone()];
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(sym) = queue.pop_front() {
+        for generator in &generators {
+            let next = sym.then(generator);
+            if !out.iter().any(|s| s.map == next.map) {
+                out.push(next.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    out
+}
+
+/// The 24 whole cube turns, starting with doing nothing
+pub fn rotations() -> Vec<Symmetry> {
+    turns_from(Symmetry::identity())
+}
+
+/// All 48 symmetries: the 24 turns, then the same turns seen in a mirror
+pub fn all_symmetries() -> Vec<Symmetry> {
+    let mirror = Symmetry {
+        map: LR_MIRROR,
+        mirrored: true,
+        name: "mirror".to_string(),
+    };
+
+    rotations().into_iter().chain(turns_from(mirror)).collect()
+}
+
+/// Just the mirror standing in this plane, with no turning
+pub fn plane_mirror(plane: Plane) -> Symmetry {
+    // the other mirrors are the L/R one turned half way around
+    let name = match plane {
+        Plane::LR => "mirror",
+        Plane::UD => "mirror z2",
+        Plane::FB => "mirror y2",
+    };
+
+    all_symmetries()
+        .into_iter()
+        .find(|s| s.name == name)
+        .expect("Every mirror is one half turn from the L/R one")
+}
+
+/// The representative of a class of symmetric states
+pub struct Canonical {
+    pub cube: Cube,
+    /// Its rank (see [crate::dist_table::rank_state]); the representative is the state with the
+    /// lowest rank, which is the lexicographically least position arrangement, and then the least
+    /// orientation arrangement
+    pub state: usize,
+    /// Takes the original state to the representative
+    pub symmetry: Symmetry,
+}
+
+/// The least state symmetric to this one under the given symmetries, and which symmetry gets
+/// there (the first one, if several do)
+pub fn canonicalize(cube: &Cube, symmetries: &[Symmetry]) -> Canonical {
+    symmetries
+        .iter()
+        .map(|symmetry| {
+            let image = symmetry.apply(cube);
+            Canonical {
+                state: rank_cube(&image),
+                cube: image,
+                symmetry: symmetry.clone(),
+            }
+        })
+        .min_by_key(|canonical| canonical.state)
+        .expect("There's always at least one symmetry")
+}
+
+/// For every rank, whether that state is the least of its class; the same as [canonicalize] on
+/// every state, but only turning over one cube per class. Going through the states in order, the
+/// first one not yet seen is the least of its class, and everything symmetric to it is then seen.
+pub fn canonical_states(symmetries: &[Symmetry]) -> Vec<bool> {
+    let mut canonical = vec![false; NUM_STATES];
+    let mut seen = vec![false; NUM_STATES];
+
+    for state in 0..NUM_STATES {
+        if seen[state] {
+            continue;
+        }
+        canonical[state] = true;
+
+        let cube = arrangements_cube(
+            &unrank_pos(state / NUM_ORIENTATIONS),
+            &unrank_orr(state % NUM_ORIENTATIONS),
+        );
+        for symmetry in symmetries {
+            seen[rank_cube(&symmetry.apply(&cube))] = true;
+        }
+    }
+
+    canonical
+}
+
+#[cfg(test)]
+mod symmetry_tests {
+    use crate::color_scheme::ColorScheme;
+    use crate::dist_table::{rank_cube, DistanceTable};
+    use crate::moves::CanFullMove;
+    use crate::scramble::full_scramble;
+
+    use super::*;
+
+    #[test]
+    fn symmetries_are_distinct() {
+        let all = all_symmetries();
+        assert_eq!(rotations().len(), 24);
+        assert_eq!(all.len(), 48);
+
+        for (i, a) in all.iter().enumerate() {
+            assert!(all[i + 1..].iter().all(|b| a.map != b.map));
+        }
+
+        assert_eq!(all.iter().filter(|s| s.mirrored).count(), 24);
+    }
+
+    #[test]
+    fn conjugates_keep_distance() {
+        let table = DistanceTable::build();
+        let solved = ColorScheme::western().solved_cube();
+
+        for sym in all_symmetries() {
+            assert!(sym.apply(&solved).solved());
+        }
+
+        // a single R turns into a single move of some other face
+        let r = solved.apply_many_full(&parse_line("R").unwrap());
+        for sym in all_symmetries() {
+            assert_eq!(table.distance(&sym.apply(&r)), 1);
+        }
+
+        for _ in 0..5 {
+            let cube = full_scramble(&ColorScheme::western());
+            let distance = table.distance(&cube);
+            for sym in all_symmetries() {
+                assert_eq!(table.distance(&sym.apply(&cube)), distance);
+            }
+        }
+    }
+
+    #[test]
+    fn arrangements_and_cubes_agree() {
+        for _ in 0..5 {
+            let cube = full_scramble(&ColorScheme::western());
+            let pos = cube.clone().make_pos_arr_from_dlb();
+            let orr = cube.clone().make_orr_arr_from_dlb();
+
+            let rebuilt = arrangements_cube(&pos, &orr);
+            assert_eq!(rank_cube(&rebuilt), rank_cube(&cube));
+
+            for sym in all_symmetries() {
+                let image = sym.apply(&cube);
+                let (image_pos, image_orr) = sym.apply_arrangements(&pos, &orr);
+                assert_eq!(image.clone().make_pos_arr_from_dlb(), image_pos);
+                assert_eq!(image.make_orr_arr_from_dlb(), image_orr);
+            }
+        }
+    }
+
+    #[test]
+    fn turning_and_recoloring() {
+        let solved = ColorScheme::western().solved_cube();
+        let y = rotations().into_iter().find(|s| s.name() == "y").unwrap();
+
+        // turning a solved cube, or repainting it, still leaves it solved, but not the same
+        assert!(y.turn(&solved).solved());
+        assert!(y.recolor(&solved).solved());
+        assert_ne!(y.turn(&solved), solved);
+
+        // and doing both is conjugating, which leaves it exactly as it was
+        assert_eq!(y.apply(&solved), solved);
+
+        // y takes the R face round to the front, so conjugating by it turns R into F
+        let r = solved.clone().apply_many_full(&parse_line("R").unwrap());
+        let f = solved.apply_many_full(&parse_line("F").unwrap());
+        assert_eq!(y.apply(&r), f);
+    }
+
+    #[test]
+    fn canonical_representatives() {
+        let symmetries = all_symmetries();
+
+        for _ in 0..5 {
+            let cube = full_scramble(&ColorScheme::western());
+            let canonical = canonicalize(&cube, &symmetries);
+
+            assert_eq!(
+                canonicalize(&canonical.cube, &symmetries).state,
+                canonical.state
+            );
+            assert_eq!(rank_cube(&canonical.symmetry.apply(&cube)), canonical.state);
+
+            // every symmetric state has the same representative, and the inverse gets back
+            for sym in &symmetries {
+                let image = sym.apply(&cube);
+                assert_eq!(canonicalize(&image, &symmetries).state, canonical.state);
+            }
+
+            let back = canonical.symmetry.inverse().apply(&canonical.cube);
+            assert_eq!(rank_cube(&back), rank_cube(&cube));
+        }
+
+        // solved is its own representative, by doing nothing
+        let solved = ColorScheme::western().solved_cube();
+        let canonical = canonicalize(&solved, &symmetries);
+        assert_eq!(canonical.symmetry.name(), "");
+        assert!(canonical.cube.solved());
+    }
+
+    #[test]
+    fn canonical_table() {
+        let symmetries = all_symmetries();
+        let canonical = canonical_states(&symmetries);
+
+        // the classes of the whole cube under all 48 symmetries
+        assert_eq!(canonical.iter().filter(|&&c| c).count(), 77_802);
+
+        for _ in 0..20 {
+            let cube = full_scramble(&ColorScheme::western());
+            let state = rank_cube(&cube);
+            assert_eq!(
+                canonical[state],
+                canonicalize(&cube, &symmetries).state == state
+            );
+
+            let representative = canonicalize(&cube, &symmetries).cube;
+            assert!(canonical[rank_cube(&representative)]);
+        }
+    }
+}
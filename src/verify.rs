@@ -0,0 +1,87 @@
+//! Checking someone else's solution to a scramble
+
+use crate::cube::Cube;
+use crate::moves::{CanFullMove, FullMove};
+
+/// Where a cube ended up after a scramble and a claimed solution
+pub struct Verdict {
+    pub state: Cube,
+    pub solved: bool,
+    /// Corners (other than DLB, which everything is measured against) in the wrong place
+    pub misplaced: usize,
+    /// Corners without a U or D color on the U or D face, whether or not they're in the right
+    /// place; the way a solver would count them, so a U turn twists nothing and an R turn twists
+    /// four
+    pub misoriented: usize,
+}
+
+pub fn verify(start: Cube, scramble: &[FullMove], solution: &[FullMove]) -> Verdict {
+    let state = start.apply_many_full(scramble).apply_many_full(solution);
+
+    let (misplaced, misoriented) = corner_errors(&state);
+
+    Verdict {
+        solved: state.solved(),
+        state,
+        misplaced,
+        misoriented,
+    }
+}
+
+/// How many corners are misplaced, and how many are misoriented, relative to DLB
+pub fn corner_errors(cube: &Cube) -> (usize, usize) {
+    let pos = cube.clone().make_pos_arr_from_dlb().to_indices();
+    let desired = cube.make_desired_from_dlb();
+
+    let misplaced = pos.iter().enumerate().filter(|(i, p)| *i != **p).count();
+
+    // every corner has exactly one facelet on the U or D face, so count those facelets instead;
+    // the internal orientations are measured along another axis, which isn't how people see it
+    let facelets = cube.to_facelets();
+    let misoriented = facelets[0..4]
+        .iter()
+        .chain(&facelets[12..16])
+        .filter(|f| **f != desired.u && **f != desired.d)
+        .count();
+
+    (misplaced, misoriented)
+}
+
+#[cfg(test)]
+mod verify_tests {
+    use crate::color_scheme::ColorScheme;
+    use crate::setup::parse_line;
+
+    use super::*;
+
+    #[test]
+    fn good_and_bad_solutions() {
+        let solved = ColorScheme::western().solved_cube();
+        let scramble = parse_line("R U R' U'").unwrap();
+
+        let good = verify(solved.clone(), &scramble, &parse_line("U R U' R'").unwrap());
+        assert!(good.solved);
+        assert_eq!((good.misplaced, good.misoriented), (0, 0));
+
+        // undoing with L moves still counts, since it's only a rotation away from solved
+        let rotated = verify(solved.clone(), &parse_line("R L'").unwrap(), &[]);
+        assert!(rotated.solved);
+
+        // sune without the last move leaves an R' to do, which moves and twists four corners
+        let bad = verify(
+            solved.clone(),
+            &parse_line("R U R' U R U2 R'").unwrap(),
+            &parse_line("R U2 R' U' R U' ").unwrap(),
+        );
+        assert!(!bad.solved);
+        assert_eq!(bad.misplaced, 4);
+        assert_eq!(bad.misoriented, 4);
+
+        // a U turn moves corners around, but keeps their U colors on U
+        let turned = verify(solved.clone(), &parse_line("U").unwrap(), &[]);
+        assert_eq!((turned.misplaced, turned.misoriented), (4, 0));
+
+        let twisted = verify(solved, &parse_line("R'").unwrap(), &[]);
+        assert_eq!((twisted.misplaced, twisted.misoriented), (4, 4));
+    }
+}